@@ -0,0 +1,124 @@
+// Static interval tree: the intervals are sorted by start and stored in a Vec,
+// which is treated as an implicit balanced binary search tree (the middle of
+// every slice is the root of that slice). Each node also stores the largest
+// end found in its subtree, so whole subtrees can be skipped when querying.
+
+#[derive(Debug)]
+struct Node<T> {
+    start: u32,
+    end: u32,
+    max_end: u32,
+    value: T,
+}
+
+#[derive(Debug)]
+pub struct IntervalTree<T> {
+    nodes: Vec<Node<T>>,
+}
+
+impl<T> IntervalTree<T> {
+    // Intervals are inclusive in both ends, like the section assignments
+    pub fn new(intervals: impl IntoIterator<Item = (u32, u32, T)>) -> Self {
+        let mut nodes: Vec<Node<T>> = intervals
+            .into_iter()
+            .map(|(start, end, value)| Node {
+                start,
+                end,
+                max_end: end,
+                value,
+            })
+            .collect();
+        nodes.sort_by_key(|node| (node.start, node.end));
+        compute_max_end(&mut nodes);
+        IntervalTree { nodes }
+    }
+
+    // All values whose interval contains `point`
+    pub fn stabbing(&self, point: u32) -> Vec<&T> {
+        self.overlapping(point, point)
+    }
+
+    // All values whose interval shares at least one section with start..=end
+    pub fn overlapping(&self, start: u32, end: u32) -> Vec<&T> {
+        let mut found = Vec::new();
+        search(&self.nodes, start, end, &mut found);
+        found
+    }
+}
+
+fn compute_max_end<T>(nodes: &mut [Node<T>]) -> u32 {
+    if nodes.is_empty() {
+        return 0;
+    }
+    let mid = nodes.len() / 2;
+    let (left, rest) = nodes.split_at_mut(mid);
+    let (node, right) = rest.split_first_mut().expect("Should not be empty");
+    node.max_end = node
+        .end
+        .max(compute_max_end(left))
+        .max(compute_max_end(right));
+    node.max_end
+}
+
+fn search<'a, T>(nodes: &'a [Node<T>], start: u32, end: u32, found: &mut Vec<&'a T>) {
+    if nodes.is_empty() {
+        return;
+    }
+    let mid = nodes.len() / 2;
+    let node = &nodes[mid];
+    // Nothing in this subtree reaches the query
+    if node.max_end < start {
+        return;
+    }
+    search(&nodes[..mid], start, end, found);
+    // Everything to the right starts after the query has ended
+    if node.start > end {
+        return;
+    }
+    if node.end >= start {
+        found.push(&node.value);
+    }
+    search(&nodes[mid + 1..], start, end, found);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn brute_force(intervals: &[(u32, u32, usize)], start: u32, end: u32) -> Vec<usize> {
+        intervals
+            .iter()
+            .filter(|(s, e, _)| *s <= end && start <= *e)
+            .map(|(_, _, i)| *i)
+            .collect()
+    }
+
+    #[test]
+    fn matches_brute_force() {
+        let intervals: Vec<(u32, u32, usize)> = (0..50)
+            .map(|i| {
+                let start = (i * 37 + 11) % 97;
+                let end = start + (i * 13) % 20;
+                (start as u32, end as u32, i)
+            })
+            .collect();
+        let tree = IntervalTree::new(intervals.clone());
+        for start in 0..120 {
+            for end in start..start + 5 {
+                let mut found: Vec<usize> =
+                    tree.overlapping(start, end).into_iter().copied().collect();
+                found.sort();
+                assert_eq!(found, brute_force(&intervals, start, end));
+            }
+        }
+    }
+
+    #[test]
+    fn stabbing_edges() {
+        let tree = IntervalTree::new(vec![(2, 4, 'a'), (4, 6, 'b'), (7, 7, 'c')]);
+        assert_eq!(tree.stabbing(1), Vec::<&char>::new());
+        assert_eq!(tree.stabbing(4), vec![&'a', &'b']);
+        assert_eq!(tree.stabbing(7), vec![&'c']);
+        assert_eq!(tree.stabbing(8), Vec::<&char>::new());
+    }
+}
@@ -1,28 +1,155 @@
-use itertools::Itertools;
+use anyhow::{anyhow, Context, Result};
 use std::io::BufRead;
 
-#[derive(Debug)]
+mod interval_tree;
+use interval_tree::IntervalTree;
+
+#[derive(Debug, PartialEq)]
 struct Elf(u32, u32);
 
-impl<S: Into<String>> From<S> for Elf {
-    // Only accepts input of the form "x-y"
-    fn from(value: S) -> Self {
+impl TryFrom<&str> for Elf {
+    type Error = anyhow::Error;
+
+    // Only accepts input of the form "x-y", with x <= y
+    fn try_from(value: &str) -> Result<Self> {
         let (start, end) = value
-            .into()
-            .split('-')
-            .map(|x| x.parse::<u32>().expect("Should not fail"))
-            .tuples()
-            .next()
-            .expect("Should not fail");
-
-        Elf(start, end)
+            .split_once('-')
+            .ok_or(anyhow!("Not a range: {:?}", value))?;
+        let start: u32 = start
+            .parse()
+            .with_context(|| format!("Invalid start of range: {:?}", value))?;
+        let end: u32 = end
+            .parse()
+            .with_context(|| format!("Invalid end of range: {:?}", value))?;
+        if start > end {
+            return Err(anyhow!("Reversed range: {:?}", value));
+        }
+        Ok(Elf(start, end))
+    }
+}
+
+#[derive(Debug, PartialEq)]
+enum Query {
+    // Which elves cover this section
+    Cover(u32),
+    // Which pairs have an elf overlapping this range
+    Overlap(Elf),
+}
+
+impl TryFrom<&str> for Query {
+    type Error = anyhow::Error;
+
+    // Accepts "cover 57" or "overlap 10-20"
+    fn try_from(value: &str) -> Result<Self> {
+        let mut words = value.split_whitespace();
+        let query = match (words.next(), words.next(), words.next()) {
+            (Some("cover"), Some(section), None) => Query::Cover(
+                section
+                    .parse()
+                    .with_context(|| format!("Invalid section: {:?}", section))?,
+            ),
+            (Some("overlap"), Some(range), None) => Query::Overlap(Elf::try_from(range)?),
+            _ => return Err(anyhow!("Not a valid query: {:?}", value)),
+        };
+        Ok(query)
     }
 }
 
-fn main() {
-    let input = read_input().expect("Should be able to read file");
-    let input = parse_input(input);
-    println!("{}", pairs_overlap(input));
+// Where an assignment came from: the line of the pair, and which of the two
+// elves it belongs to (both counted from 1)
+#[derive(Debug, PartialEq, Clone, Copy)]
+struct Assignment {
+    line: usize,
+    elf: usize,
+}
+
+fn main() -> Result<()> {
+    let input = read_input()?;
+    let input = parse_input(input)?;
+    let mut args = std::env::args().skip(1);
+    match args.next().as_deref() {
+        None => {
+            println!("Part 1: Fully contained pairs: {}", pairs_contained(&input));
+            println!("Part 2: Overlapping pairs: {}", pairs_overlap(&input));
+        }
+        Some("query") => {
+            let tree = build_tree(&input);
+            let query = args.collect::<Vec<String>>().join(" ");
+            if query.is_empty() {
+                // No query on the command line, so read one per line from stdin
+                for line in std::io::stdin().lock().lines() {
+                    let line = line?;
+                    if !line.trim().is_empty() {
+                        answer_query(&tree, &input, Query::try_from(line.as_str())?);
+                    }
+                }
+            } else {
+                answer_query(&tree, &input, Query::try_from(query.as_str())?);
+            }
+        }
+        Some(x) => return Err(anyhow!("Unknown mode: {}", x)),
+    }
+    Ok(())
+}
+
+fn build_tree(pairs: &[(Elf, Elf)]) -> IntervalTree<Assignment> {
+    IntervalTree::new(pairs.iter().zip(1..).flat_map(|((elf1, elf2), line)| {
+        [
+            (elf1.0, elf1.1, Assignment { line, elf: 1 }),
+            (elf2.0, elf2.1, Assignment { line, elf: 2 }),
+        ]
+    }))
+}
+
+fn elves_covering(tree: &IntervalTree<Assignment>, section: u32) -> Vec<Assignment> {
+    let mut found: Vec<Assignment> = tree.stabbing(section).into_iter().copied().collect();
+    found.sort_by_key(|a| (a.line, a.elf));
+    found
+}
+
+fn pairs_overlapping(tree: &IntervalTree<Assignment>, range: &Elf) -> Vec<usize> {
+    let mut found: Vec<usize> = tree
+        .overlapping(range.0, range.1)
+        .into_iter()
+        .map(|a| a.line)
+        .collect();
+    // Only report each pair once, even if both elves overlap
+    found.sort();
+    found.dedup();
+    found
+}
+
+fn answer_query(tree: &IntervalTree<Assignment>, pairs: &[(Elf, Elf)], query: Query) {
+    match query {
+        Query::Cover(section) => {
+            let found = elves_covering(tree, section);
+            println!("{} elves cover section {}:", found.len(), section);
+            for Assignment { line, elf } in found {
+                let Elf(start, end) = if elf == 1 {
+                    &pairs[line - 1].0
+                } else {
+                    &pairs[line - 1].1
+                };
+                println!("  line {}, elf {}: {}-{}", line, elf, start, end);
+            }
+        }
+        Query::Overlap(range) => {
+            let found = pairs_overlapping(tree, &range);
+            println!(
+                "{} pairs overlap range {}-{}:",
+                found.len(),
+                range.0,
+                range.1
+            );
+            for line in found {
+                let (elf1, elf2) = &pairs[line - 1];
+                println!(
+                    "  line {}: {}-{},{}-{}",
+                    line, elf1.0, elf1.1, elf2.0, elf2.1
+                );
+            }
+        }
+    }
 }
 
 fn read_input() -> Result<Vec<String>, std::io::Error> {
@@ -32,37 +159,37 @@ fn read_input() -> Result<Vec<String>, std::io::Error> {
     buf.lines().collect()
 }
 
-fn parse_line(line: String) -> (Elf, Elf) {
-    let mut line = line.split(',');
-    (
-        Elf::from(line.next().expect("No first elf")),
-        Elf::from(line.next().expect("No second elf")),
-    )
+fn parse_line(line: &str) -> Result<(Elf, Elf)> {
+    let (elf1, elf2) = line
+        .split_once(',')
+        .ok_or(anyhow!("Not a pair: {:?}", line))?;
+    Ok((Elf::try_from(elf1)?, Elf::try_from(elf2)?))
 }
 
-fn parse_input(input: Vec<String>) -> Vec<(Elf, Elf)> {
-    input.into_iter().map(|l| parse_line(l)).collect()
+fn parse_input(input: Vec<String>) -> Result<Vec<(Elf, Elf)>> {
+    input
+        .iter()
+        .enumerate()
+        .map(|(i, l)| parse_line(l).with_context(|| format!("Line {}", i + 1)))
+        .collect()
 }
 
-fn _contained(elf1: &Elf, elf2: &Elf) -> bool {
+fn contained(elf1: &Elf, elf2: &Elf) -> bool {
     elf1.0 <= elf2.0 && elf1.1 >= elf2.1 || elf1.0 >= elf2.0 && elf1.1 <= elf2.1
 }
 
 fn overlaps(elf1: &Elf, elf2: &Elf) -> bool {
-    elf2.0 <= elf1.0 && elf1.0 <= elf2.1
-        || elf2.0 <= elf1.1 && elf1.0 <= elf2.1
-        || elf1.0 <= elf2.0 && elf2.0 <= elf1.1
-        || elf1.0 <= elf2.1 && elf2.0 <= elf1.1
+    elf1.0 <= elf2.1 && elf2.0 <= elf1.1
 }
 
-fn _pairs_contained(pairs: Vec<(Elf, Elf)>) -> usize {
+fn pairs_contained(pairs: &[(Elf, Elf)]) -> usize {
     pairs
         .iter()
-        .filter(|(elf1, elf2)| _contained(elf1, elf2))
+        .filter(|(elf1, elf2)| contained(elf1, elf2))
         .count()
 }
 
-fn pairs_overlap(pairs: Vec<(Elf, Elf)>) -> usize {
+fn pairs_overlap(pairs: &[(Elf, Elf)]) -> usize {
     pairs
         .iter()
         .filter(|(elf1, elf2)| overlaps(elf1, elf2))
@@ -74,7 +201,7 @@ mod tests {
     use super::*;
 
     #[test]
-    fn small_sample_contains() {
+    fn small_sample_contains() -> Result<()> {
         let input = "2-4,6-8\n\
         2-3,4-5\n\
         5-7,7-9\n\
@@ -83,14 +210,15 @@ mod tests {
         2-6,4-8";
         let input: Vec<String> = input.split('\n').map(|l| l.to_owned()).collect();
         dbg!(&input);
-        let input = parse_input(input);
+        let input = parse_input(input)?;
         dbg!(&input);
 
-        assert_eq!(_pairs_contained(input), 2)
+        assert_eq!(pairs_contained(&input), 2);
+        Ok(())
     }
 
     #[test]
-    fn small_sample_overlaps() {
+    fn small_sample_overlaps() -> Result<()> {
         let input = "2-4,6-8\n\
         2-3,4-5\n\
         5-7,7-9\n\
@@ -99,9 +227,64 @@ mod tests {
         2-6,4-8";
         let input: Vec<String> = input.split('\n').map(|l| l.to_owned()).collect();
         dbg!(&input);
-        let input = parse_input(input);
+        let input = parse_input(input)?;
         dbg!(&input);
 
-        assert_eq!(pairs_overlap(input), 4)
+        assert_eq!(pairs_overlap(&input), 4);
+        Ok(())
+    }
+
+    #[test]
+    fn sample_queries() -> Result<()> {
+        let input = "2-4,6-8\n\
+        2-3,4-5\n\
+        5-7,7-9\n\
+        2-8,3-7\n\
+        6-6,4-6\n\
+        2-6,4-8";
+        let input: Vec<String> = input.split('\n').map(|l| l.to_owned()).collect();
+        let input = parse_input(input)?;
+        let tree = build_tree(&input);
+
+        let cover: Vec<(usize, usize)> = elves_covering(&tree, 7)
+            .iter()
+            .map(|a| (a.line, a.elf))
+            .collect();
+        assert_eq!(cover, vec![(1, 2), (3, 1), (3, 2), (4, 1), (4, 2), (6, 2)]);
+
+        assert_eq!(pairs_overlapping(&tree, &Elf(1, 3)), vec![1, 2, 4, 6]);
+        assert_eq!(pairs_overlapping(&tree, &Elf(10, 20)), Vec::<usize>::new());
+        Ok(())
+    }
+
+    #[test]
+    fn invalid_queries() {
+        assert!(Query::try_from("cover").is_err());
+        assert!(Query::try_from("cover x").is_err());
+        assert!(Query::try_from("overlap 20-10").is_err());
+        assert!(Query::try_from("find 3").is_err());
+    }
+
+    #[test]
+    fn parse_elf() -> Result<()> {
+        assert_eq!(Elf::try_from("3-5")?, Elf(3, 5));
+        assert_eq!(Elf::try_from("6-6")?, Elf(6, 6));
+        Ok(())
+    }
+
+    #[test]
+    fn invalid_elves() {
+        assert!(Elf::try_from("5-3").is_err());
+        assert!(Elf::try_from("a-b").is_err());
+        assert!(Elf::try_from("5").is_err());
+        assert!(Elf::try_from("-5").is_err());
+    }
+
+    #[test]
+    fn error_reports_line_number() {
+        let input = vec!["2-4,6-8".to_owned(), "2-3,5-4".to_owned()];
+        let error = parse_input(input).unwrap_err();
+        assert_eq!(error.to_string(), "Line 2");
+        assert!(format!("{:#}", error).contains("Reversed range"));
     }
 }
@@ -0,0 +1,247 @@
+use anyhow::{anyhow, Context, Result};
+
+use crate::stacks::Stacks;
+
+// Something in the drawing, and the columns it covers (counted in chars,
+// `end` not included)
+#[derive(Debug, PartialEq)]
+struct Token {
+    start: usize,
+    end: usize,
+    text: String,
+}
+
+// Parses the drawing of the stacks, top line first, with the row of stack
+// numbers as the last line. Crates belong to the stack whose number is
+// underneath them, so labels can be any width and trailing spaces don't
+// matter.
+pub fn parse_drawing(lines: &[String]) -> Result<Stacks> {
+    let (number_line, crate_lines) = lines.split_last().ok_or(anyhow!("The drawing is empty"))?;
+    let numbers = parse_numbers(number_line)
+        .with_context(|| format!("Line {}: {:?}", lines.len(), number_line))?;
+    let mut stacks = Stacks::new(numbers.len());
+    // Fill the stacks from the bottom up
+    for (level, (line, line_nr)) in crate_lines
+        .iter()
+        .zip(1..crate_lines.len() + 1)
+        .rev()
+        .enumerate()
+    {
+        parse_crate_line(line, &numbers, level, &mut stacks)
+            .with_context(|| format!("Line {}: {:?}", line_nr, line))?;
+    }
+    Ok(stacks)
+}
+
+// Returns the column under the middle of each stack number
+fn parse_numbers(line: &str) -> Result<Vec<usize>> {
+    let mut columns = Vec::new();
+    for (token, expected) in split_whitespace(line).into_iter().zip(1..) {
+        let number: usize = token
+            .text
+            .parse()
+            .with_context(|| format!("Not a stack number: {:?}", token.text))?;
+        if number != expected {
+            return Err(anyhow!(
+                "Stack {} at column {} should have been stack {}",
+                number,
+                token.start,
+                expected
+            ));
+        }
+        columns.push((token.start + token.end - 1) / 2);
+    }
+    if columns.is_empty() {
+        return Err(anyhow!("No stack numbers"));
+    }
+    Ok(columns)
+}
+
+fn parse_crate_line(
+    line: &str,
+    numbers: &[usize],
+    level: usize,
+    stacks: &mut Stacks,
+) -> Result<()> {
+    for token in split_crates(line)? {
+        let mut below = numbers
+            .iter()
+            .zip(1..)
+            .filter(|(&column, _)| token.start <= column && column < token.end)
+            .map(|(_, number)| number);
+        let number = match (below.next(), below.next()) {
+            (Some(number), None) => number,
+            (None, _) => {
+                return Err(anyhow!(
+                    "Crate {} at column {} is not above any stack number",
+                    token.text,
+                    token.start
+                ))
+            }
+            (Some(_), Some(_)) => {
+                return Err(anyhow!(
+                    "Crate {} at column {} is above more than one stack number",
+                    token.text,
+                    token.start
+                ))
+            }
+        };
+        let stack = &mut stacks[number];
+        if stack.len() != level {
+            return Err(anyhow!(
+                "Crate {} at column {} is not resting on top of stack {}",
+                token.text,
+                token.start,
+                number
+            ));
+        }
+        let label = &token.text[1..token.text.len() - 1];
+        stack.push(label.to_owned());
+    }
+    Ok(())
+}
+
+fn split_whitespace(line: &str) -> Vec<Token> {
+    let mut tokens: Vec<Token> = Vec::new();
+    let mut current: Option<Token> = None;
+    for (column, c) in line.chars().enumerate() {
+        match (c.is_whitespace(), current.as_mut()) {
+            (true, _) => tokens.extend(current.take()),
+            (false, Some(token)) => {
+                token.text.push(c);
+                token.end = column + 1;
+            }
+            (false, None) => {
+                current = Some(Token {
+                    start: column,
+                    end: column + 1,
+                    text: c.to_string(),
+                })
+            }
+        }
+    }
+    tokens.extend(current);
+    tokens
+}
+
+// Finds every "[label]" in the line. Crates may touch each other, but anything
+// else outside the brackets is an error.
+fn split_crates(line: &str) -> Result<Vec<Token>> {
+    let mut tokens = Vec::new();
+    let mut chars = line.chars().enumerate();
+    while let Some((start, c)) = chars.next() {
+        match c {
+            '[' => {
+                let mut text = String::from('[');
+                let end = loop {
+                    match chars.next() {
+                        Some((column, ']')) => break column + 1,
+                        Some((_, '[')) | None => {
+                            return Err(anyhow!("Unclosed crate at column {}", start))
+                        }
+                        Some((_, c)) => text.push(c),
+                    }
+                };
+                text.push(']');
+                if text.len() == 2 || text.contains(char::is_whitespace) {
+                    return Err(anyhow!("Invalid crate label {} at column {}", text, start));
+                }
+                tokens.push(Token { start, end, text });
+            }
+            c if c.is_whitespace() => (),
+            c => {
+                return Err(anyhow!(
+                    "Unexpected {:?} at column {}, crates should look like [A]",
+                    c,
+                    start
+                ))
+            }
+        }
+    }
+    Ok(tokens)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lines(drawing: &str) -> Vec<String> {
+        drawing.lines().map(|l| l.to_owned()).collect()
+    }
+
+    #[test]
+    fn simple_stack() -> Result<()> {
+        assert_eq!(parse_numbers(" 1   2   3 ")?, vec![1, 5, 9]);
+        assert!(parse_numbers(" 1   3 ").is_err());
+        assert!(parse_numbers(" 1   x ").is_err());
+        assert!(parse_numbers("   ").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn one_line() -> Result<()> {
+        let mut stacks = Stacks::new(3);
+        parse_crate_line("[Z] [M]    ", &[1, 5, 9], 0, &mut stacks)?;
+        assert_eq!(stacks[1], vec!["Z"]);
+        assert_eq!(stacks[2], vec!["M"]);
+        assert_eq!(stacks[3], Vec::<String>::new());
+        Ok(())
+    }
+
+    #[test]
+    fn ragged_lines() -> Result<()> {
+        let stacks = parse_drawing(&lines("    [D]\n[N] [C]\n[Z] [M] [P]\n 1   2   3"))?;
+        assert_eq!(stacks.top_crates(), "NDP");
+        assert_eq!(stacks[2], vec!["M", "C", "D"]);
+        Ok(())
+    }
+
+    #[test]
+    fn wide_drawing() -> Result<()> {
+        let drawing = "\
+[AB]                                       [XYZ]
+[CD] [EF]                                  [UVW]
+ 1    2    3    4    5    6    7    8    9    10";
+        let stacks = parse_drawing(&lines(drawing))?;
+        assert_eq!(stacks.len(), 10);
+        assert_eq!(stacks[1], vec!["CD", "AB"]);
+        assert_eq!(stacks[2], vec!["EF"]);
+        assert_eq!(stacks[10], vec!["UVW", "XYZ"]);
+        assert_eq!(stacks.top_crates(), "ABEFXYZ");
+        Ok(())
+    }
+
+    #[test]
+    fn display_round_trip() -> Result<()> {
+        let drawing = "\
+[AB]                                       [XYZ]
+[CD] [EF]                                  [UVW]
+ 1    2    3    4    5    6    7    8    9    10";
+        let stacks = parse_drawing(&lines(drawing))?;
+        assert_eq!(parse_drawing(&lines(&stacks.to_string()))?, stacks);
+        Ok(())
+    }
+
+    #[test]
+    fn misaligned_crates() {
+        // Between two stacks
+        assert!(parse_drawing(&lines("  [A]\n 1   2")).is_err());
+        // Past the last stack
+        assert!(parse_drawing(&lines("        [A]\n 1   2")).is_err());
+        // Wide enough to cover two stacks
+        assert!(parse_drawing(&lines("[ABCDEF]\n 1   2")).is_err());
+        // Floating above an empty stack
+        assert!(parse_drawing(&lines("[A]\n   \n 1   2")).is_err());
+        // Not a crate
+        assert!(parse_drawing(&lines(" A \n 1 ")).is_err());
+        assert!(parse_drawing(&lines("[A\n 1 ")).is_err());
+        assert!(parse_drawing(&lines("[]\n 1 ")).is_err());
+        assert!(parse_drawing(&[]).is_err());
+    }
+
+    #[test]
+    fn error_names_line() {
+        let error = parse_drawing(&lines("[A]\n[B]   [C]\n 1   2")).unwrap_err();
+        assert!(error.to_string().starts_with("Line 2: "));
+    }
+}
@@ -0,0 +1,191 @@
+use anyhow::{anyhow, Context, Result};
+use rand::rngs::StdRng;
+use rand::seq::index;
+use rand::{Rng, SeedableRng};
+use std::fmt;
+
+use crate::stacks::Stacks;
+use crate::{CraneModel, MoveInstructions};
+
+// A starting drawing and a list of moves, written out like a puzzle input
+#[derive(Debug)]
+pub struct Puzzle {
+    pub stacks: Stacks,
+    pub instructions: Vec<MoveInstructions>,
+}
+
+impl fmt::Display for Puzzle {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "{}\n", self.stacks)?;
+        for instruction in &self.instructions {
+            writeln!(f, "{}", instruction)?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub struct GeneratorOptions {
+    pub crane: CraneModel,
+    pub moves: usize,
+    // Most crates put underneath each top crate when building the end state
+    pub filler: usize,
+    pub seed: u64,
+}
+
+impl Default for GeneratorOptions {
+    fn default() -> Self {
+        GeneratorOptions {
+            crane: CraneModel::CrateMover9001,
+            moves: 20,
+            filler: 4,
+            seed: 0,
+        }
+    }
+}
+
+impl GeneratorOptions {
+    // Accepts "--crane 9000", "--moves <n>", "--filler <n>" and "--seed <n>"
+    pub fn from_args(args: impl IntoIterator<Item = String>) -> Result<Self> {
+        let mut options = GeneratorOptions::default();
+        let mut args = args.into_iter();
+        while let Some(flag) = args.next() {
+            let value = args.next().ok_or(anyhow!("Missing value after {}", flag))?;
+            let invalid = || format!("Invalid value for {}: {}", flag, value);
+            match flag.as_str() {
+                "--crane" => options.crane = CraneModel::try_from(value.as_str())?,
+                "--moves" => options.moves = value.parse().with_context(invalid)?,
+                "--filler" => options.filler = value.parse().with_context(invalid)?,
+                "--seed" => options.seed = value.parse().with_context(invalid)?,
+                x => return Err(anyhow!("Unknown flag: {}", x)),
+            }
+        }
+        Ok(options)
+    }
+}
+
+// Builds a puzzle whose answer is `target`, one crate label per char. The end
+// state is made up first, with the target crates on top of randomly chosen
+// stacks, and then random moves are undone from it to get the starting
+// drawing (see History for why a reversed move undoes a move).
+pub fn generate(target: &str, stack_count: usize, options: &GeneratorOptions) -> Result<Puzzle> {
+    let target: Vec<char> = target.chars().collect();
+    if stack_count == 0 {
+        return Err(anyhow!("There has to be at least one stack"));
+    }
+    if target.len() > stack_count {
+        return Err(anyhow!(
+            "Can't put {} top crates on {} stacks",
+            target.len(),
+            stack_count
+        ));
+    }
+    if let Some(c) = target
+        .iter()
+        .find(|c| c.is_whitespace() || **c == '[' || **c == ']')
+    {
+        return Err(anyhow!("Can't draw a crate labelled {:?}", c));
+    }
+
+    let mut rng = StdRng::seed_from_u64(options.seed);
+    let mut stacks = Stacks::new(stack_count);
+    let mut filled = index::sample(&mut rng, stack_count, target.len()).into_vec();
+    filled.sort();
+    for (index, top) in filled.into_iter().zip(target) {
+        let stack = &mut stacks[index + 1];
+        for _ in 0..rng.gen_range(0..=options.filler) {
+            stack.push(rng.gen_range('A'..='Z').to_string());
+        }
+        stack.push(top.to_string());
+    }
+
+    let mut instructions = Vec::new();
+    let total: usize = (1..=stack_count).map(|n| stacks[n].len()).sum();
+    // With nothing to move, or nowhere to move it, the puzzle has no moves
+    if stack_count > 1 && total > 0 {
+        for _ in 0..options.moves {
+            let from = loop {
+                let n = rng.gen_range(1..=stack_count);
+                if !stacks[n].is_empty() {
+                    break n;
+                }
+            };
+            let to = loop {
+                let n = rng.gen_range(1..=stack_count);
+                if n != from {
+                    break n;
+                }
+            };
+            let amount = rng.gen_range(1..=stacks[from].len());
+            stacks.apply(&MoveInstructions { amount, from, to }, options.crane)?;
+            // Going forwards, the crates go back where they were
+            instructions.push(MoveInstructions {
+                amount,
+                from: to,
+                to: from,
+            });
+        }
+    }
+    instructions.reverse();
+
+    Ok(Puzzle {
+        stacks,
+        instructions,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{parse_input, simulate};
+
+    #[test]
+    fn round_trip() -> Result<()> {
+        for crane in [CraneModel::CrateMover9000, CraneModel::CrateMover9001] {
+            for seed in 0..20 {
+                let options = GeneratorOptions {
+                    crane,
+                    seed,
+                    ..GeneratorOptions::default()
+                };
+                let stack_count = 3 + seed as usize % 8;
+                let puzzle = generate("CMZ", stack_count, &options)?;
+                assert_eq!(puzzle.instructions.len(), 20);
+                let input: Vec<String> = puzzle.to_string().lines().map(String::from).collect();
+                let (stacks, instructions) = parse_input(input)?;
+                assert_eq!(stacks, puzzle.stacks);
+                assert_eq!(simulate(stacks, &instructions, crane)?, "CMZ");
+            }
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn same_seed_same_puzzle() -> Result<()> {
+        let options = GeneratorOptions::default();
+        let first = generate("HELLO", 12, &options)?.to_string();
+        let second = generate("HELLO", 12, &options)?.to_string();
+        assert_eq!(first, second);
+        Ok(())
+    }
+
+    #[test]
+    fn nothing_to_move() -> Result<()> {
+        let options = GeneratorOptions::default();
+        let puzzle = generate("", 3, &options)?;
+        assert!(puzzle.instructions.is_empty());
+        let puzzle = generate("A", 1, &options)?;
+        assert!(puzzle.instructions.is_empty());
+        assert_eq!(puzzle.stacks.top_crates(), "A");
+        Ok(())
+    }
+
+    #[test]
+    fn invalid_targets() {
+        let options = GeneratorOptions::default();
+        assert!(generate("ABCD", 3, &options).is_err());
+        assert!(generate("A", 0, &options).is_err());
+        assert!(generate("A]", 3, &options).is_err());
+        assert!(GeneratorOptions::from_args(["--moves", "x"].map(String::from)).is_err());
+    }
+}
@@ -0,0 +1,221 @@
+use anyhow::{anyhow, Context, Result};
+use std::io::{BufRead, Write};
+
+use crate::stacks::Stacks;
+use crate::{CraneModel, MoveInstructions};
+
+// Steps back and forth through a list of instructions. Undoing a move is the
+// same move in the opposite direction: the CrateMover 9000 reverses the crates
+// a second time, and the CrateMover 9001 keeps them in order both ways, so no
+// copies of the stacks are needed.
+#[derive(Debug)]
+pub struct History {
+    stacks: Stacks,
+    crane: CraneModel,
+    instructions: Vec<MoveInstructions>,
+    // Number of instructions applied so far
+    position: usize,
+}
+
+impl History {
+    pub fn new(stacks: Stacks, instructions: Vec<MoveInstructions>, crane: CraneModel) -> Self {
+        History {
+            stacks,
+            crane,
+            instructions,
+            position: 0,
+        }
+    }
+
+    pub fn stacks(&self) -> &Stacks {
+        &self.stacks
+    }
+
+    pub fn position(&self) -> usize {
+        self.position
+    }
+
+    pub fn len(&self) -> usize {
+        self.instructions.len()
+    }
+
+    // Applies the next instruction, or returns false if there are none left
+    pub fn redo(&mut self) -> Result<bool> {
+        let Some(instruction) = self.instructions.get(self.position) else {
+            return Ok(false);
+        };
+        self.stacks
+            .apply(instruction, self.crane)
+            .with_context(|| format!("Instruction {}: {}", self.position + 1, instruction))?;
+        self.position += 1;
+        Ok(true)
+    }
+
+    // Takes back the last applied instruction, or returns false if there are none
+    pub fn undo(&mut self) -> Result<bool> {
+        if self.position == 0 {
+            return Ok(false);
+        }
+        let instruction = &self.instructions[self.position - 1];
+        let inverse = MoveInstructions {
+            amount: instruction.amount,
+            from: instruction.to,
+            to: instruction.from,
+        };
+        self.stacks
+            .apply(&inverse, self.crane)
+            .with_context(|| format!("Undoing instruction {}: {}", self.position, instruction))?;
+        self.position -= 1;
+        Ok(true)
+    }
+
+    // Moves to the state after `step` instructions have been applied
+    pub fn goto(&mut self, step: usize) -> Result<()> {
+        if step > self.len() {
+            return Err(anyhow!(
+                "Can't go to step {}, there are only {} instructions",
+                step,
+                self.len()
+            ));
+        }
+        while self.position < step {
+            self.redo()?;
+        }
+        while self.position > step {
+            self.undo()?;
+        }
+        Ok(())
+    }
+
+    // Replays forward from the current position until stack `number` has
+    // exactly `height` crates. Returns the step where that happened, or None
+    // (having replayed everything) if it never does.
+    pub fn replay_until(&mut self, number: usize, height: usize) -> Result<Option<usize>> {
+        loop {
+            let stack = self
+                .stacks
+                .get(number)
+                .ok_or(anyhow!("No such stack: {}", number))?;
+            if stack.len() == height {
+                return Ok(Some(self.position));
+            }
+            if !self.redo()? {
+                return Ok(None);
+            }
+        }
+    }
+
+    // Reads commands, one per line, and shows the stacks after each of them:
+    //   undo [n], redo [n], goto <step>, until <stack> <height>, show
+    pub fn run_commands(&mut self, input: impl BufRead, out: &mut impl Write) -> Result<()> {
+        for line in input.lines() {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            if let Err(error) = self.run_command(&line, out) {
+                writeln!(out, "Error: {:#}", error)?;
+                continue;
+            }
+            writeln!(out, "After step {}/{}:", self.position(), self.len())?;
+            writeln!(out, "{}\n", self.stacks)?;
+        }
+        Ok(())
+    }
+
+    fn run_command(&mut self, line: &str, out: &mut impl Write) -> Result<()> {
+        let words: Vec<&str> = line.split_whitespace().collect();
+        match words.as_slice() {
+            ["show"] => (),
+            ["undo"] => {
+                self.undo()?;
+            }
+            ["redo"] => {
+                self.redo()?;
+            }
+            ["undo", n] => self.goto(self.position.saturating_sub(n.parse()?))?,
+            ["redo", n] => self.goto(self.position.saturating_add(n.parse()?).min(self.len()))?,
+            ["goto", step] => self.goto(step.parse()?)?,
+            ["until", number, height] => {
+                if self
+                    .replay_until(number.parse()?, height.parse()?)?
+                    .is_none()
+                {
+                    writeln!(out, "Stack {} never has height {}", number, height)?;
+                }
+            }
+            _ => return Err(anyhow!("Unknown command: {}", line)),
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{parse_input, read_input};
+
+    fn small_sample(crane: CraneModel) -> Result<History> {
+        let (stacks, instructions) = parse_input(read_input("test-input.txt")?)?;
+        Ok(History::new(stacks, instructions, crane))
+    }
+
+    #[test]
+    fn undo_restores_stacks() -> Result<()> {
+        for crane in [CraneModel::CrateMover9000, CraneModel::CrateMover9001] {
+            let mut history = small_sample(crane)?;
+            let start = history.stacks().clone();
+            let mut states = vec![start.clone()];
+            while history.redo()? {
+                states.push(history.stacks().clone());
+            }
+            assert_eq!(history.position(), 4);
+            while history.undo()? {
+                assert_eq!(history.stacks(), &states[history.position()]);
+            }
+            assert_eq!(history.stacks(), &start);
+            assert!(!history.undo()?);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn goto_and_replay_until() -> Result<()> {
+        let mut history = small_sample(CraneModel::CrateMover9000)?;
+        history.goto(4)?;
+        assert_eq!(history.stacks().top_crates(), "CMZ");
+        history.goto(1)?;
+        assert_eq!(
+            history.stacks().to_string().lines().next(),
+            Some("[D]        ")
+        );
+        assert!(history.goto(5).is_err());
+
+        history.goto(0)?;
+        // Stack 3 first reaches four crates after the second instruction
+        assert_eq!(history.replay_until(3, 4)?, Some(2));
+        assert_eq!(history.replay_until(1, 5)?, None);
+        assert_eq!(history.position(), 4);
+        assert!(history.replay_until(4, 0).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn commands() -> Result<()> {
+        let mut history = small_sample(CraneModel::CrateMover9001)?;
+        let input = "redo 4\nundo 2\nuntil 2 0\nfly\ngoto x\n";
+        let mut out = Vec::new();
+        history.run_commands(input.as_bytes(), &mut out)?;
+        let out = String::from_utf8(out)?;
+        assert!(out.contains("After step 4/4:\n"));
+        assert!(out.contains("After step 2/4:\n"));
+        assert!(out.contains("Error: Unknown command: fly"));
+        assert!(out.contains("Error: invalid digit"));
+        assert_eq!(history.position(), 3);
+        // Redoing more steps than there are stops at the last one
+        let mut out = Vec::new();
+        history.run_commands("redo 18446744073709551615\n".as_bytes(), &mut out)?;
+        assert_eq!(history.position(), 4);
+        Ok(())
+    }
+}
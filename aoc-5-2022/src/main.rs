@@ -1,36 +1,55 @@
 // use std::error::Error;
-use anyhow::Result;
-use itertools::Itertools;
-use std::collections::HashMap;
+use anyhow::{anyhow, Context, Result};
+use std::fmt;
 use std::fs::File;
 use std::io::{BufRead, BufReader};
 
-// #[derive(Debug)]
-// struct _CrateStacks(HashMap<u32, Vec<char>>);
+mod stacks;
+use stacks::Stacks;
+
+mod drawing;
+use drawing::parse_drawing;
+
+mod trace;
+use trace::{trace, TraceOptions};
+
+mod history;
+use history::History;
+
+mod generator;
+use generator::{generate, GeneratorOptions};
+
+// Which crane is doing the moving
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum CraneModel {
+    // Moves one crate at a time, so moved crates end up in reverse order
+    CrateMover9000,
+    // Moves all crates at once, so moved crates keep their order
+    CrateMover9001,
+}
+
+impl TryFrom<&str> for CraneModel {
+    type Error = anyhow::Error;
+
+    fn try_from(value: &str) -> Result<Self> {
+        match value {
+            "9000" => Ok(CraneModel::CrateMover9000),
+            "9001" => Ok(CraneModel::CrateMover9001),
+            x => Err(anyhow!("Not a valid crane model: {}", x)),
+        }
+    }
+}
 
 #[derive(Debug, PartialEq)]
 struct MoveInstructions {
-    amount: u32,
-    from: u32,
-    to: u32,
+    amount: usize,
+    from: usize,
+    to: usize,
 }
 
-impl MoveInstructions {
-    fn do_instruction(&self, crate_stack: &mut HashMap<u32, Vec<char>>) {
-        let stack_from = crate_stack
-            .get_mut(&self.from)
-            .expect("Should be stack taking from");
-        let mut moved_crates = Vec::new();
-        for _ in 0..self.amount {
-            moved_crates.push(stack_from.pop().expect("Should be crate  to move"));
-        }
-        let stack_to = crate_stack
-            .get_mut(&self.to)
-            .expect("Should be stack moving to");
-        // moved_crates.reverse();
-        for crat in moved_crates.into_iter().rev() {
-            stack_to.push(crat);
-        }
+impl fmt::Display for MoveInstructions {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "move {} from {} to {}", self.amount, self.from, self.to)
     }
 }
 
@@ -39,28 +58,59 @@ fn main() {
 }
 
 fn run() -> Result<()> {
+    let mut args = std::env::args().skip(1);
+    let mode = args.next();
+    if mode.as_deref() == Some("generate") {
+        // Doesn't need the puzzle input, so handled before reading it
+        let target = args.next().ok_or(anyhow!("Missing target top crates"))?;
+        let stack_count = args
+            .next()
+            .ok_or(anyhow!("Missing number of stacks"))?
+            .parse()?;
+        let options = GeneratorOptions::from_args(args)?;
+        print!("{}", generate(&target, stack_count, &options)?);
+        return Ok(());
+    }
     let input = read_input("input.txt")?;
-    let (mut stacks, instructions) = parse_input(input);
-    for instruction in &instructions {
-        instruction.do_instruction(&mut stacks);
-
+    let (stacks, instructions) = parse_input(input)?;
+    match mode.as_deref() {
+        None => (),
+        Some("trace") => {
+            let options = TraceOptions::from_args(args)?;
+            let stacks = trace(stacks, &instructions, &options, &mut std::io::stdout())?;
+            println!("Top crates: {}", stacks.top_crates());
+            return Ok(());
+        }
+        Some("replay") => {
+            let crane = match args.next() {
+                Some(crane) => CraneModel::try_from(crane.as_str())?,
+                None => CraneModel::CrateMover9001,
+            };
+            let mut history = History::new(stacks, instructions, crane);
+            println!("{}\n", history.stacks());
+            history.run_commands(std::io::stdin().lock(), &mut std::io::stdout())?;
+            return Ok(());
+        }
+        Some(x) => return Err(anyhow!("Unknown mode: {}", x)),
     }
-    println!("{}", get_answer(stacks));
+    let result1 = simulate(stacks.clone(), &instructions, CraneModel::CrateMover9000)?;
+    println!("Part 1: Top crates with CrateMover 9000: {}", result1);
+    let result2 = simulate(stacks, &instructions, CraneModel::CrateMover9001)?;
+    println!("Part 2: Top crates with CrateMover 9001: {}", result2);
     Ok(())
 }
 
-fn get_answer(crate_stack: HashMap<u32, Vec<char>>) -> String {
-    let mut output = String::new();
-    let mut stack_nr: u32 = 1;
-    loop {
-        if let Some(stack) =crate_stack.get(&stack_nr) {
-            output.push(stack.last().expect("No stack should be empty").to_owned());
-        } else {
-            break;
-        }
-        stack_nr += 1;
+fn simulate(
+    mut stacks: Stacks,
+    instructions: &[MoveInstructions],
+    crane: CraneModel,
+) -> Result<String> {
+    for (instruction, step) in instructions.iter().zip(1..) {
+        stacks
+            .apply(instruction, crane)
+            .with_context(|| format!("Instruction {}: {:?}", step, instruction))?;
     }
-    output
+    Ok(stacks.top_crates())
 }
 
 // Using "anyhow" for error handeling here
@@ -73,59 +123,29 @@ fn read_input(input_file: &str) -> Result<Vec<String>> {
     buf.lines().map(|l| Ok(l?)).collect()
 }
 
-fn parse_input(input: Vec<String>) -> (HashMap<u32, Vec<char>>, Vec<MoveInstructions>) {
+fn parse_input(input: Vec<String>) -> Result<(Stacks, Vec<MoveInstructions>)> {
     let mut input = input.into_iter();
-    let mut stacks = input
-        .by_ref()
-        .take_while(|l| l != "")
-        .collect::<Vec<String>>()
-        .into_iter()
-        .rev();
-    let mut crate_stacks = initialize_stacks(stacks.next().expect("First line should be here"));
-
-    // dbg!(&crate_stacks);
-
-    for stack in stacks {
-        parse_stack_line(stack, &mut crate_stacks);
-    }
-    let instructions = input;
-
-    // dbg!(&instructions);
+    let drawing: Vec<String> = input.by_ref().take_while(|l| !l.is_empty()).collect();
+    let crate_stacks = parse_drawing(&drawing)?;
 
-    let instructions = instructions
-        .map(|line| parse_instruction_line(line))
-        .collect();
+    let instructions = input.map(parse_instruction_line).collect();
 
-    (crate_stacks, instructions)
-}
-
-fn parse_stack_line(line: String, crate_stacks: &mut HashMap<u32, Vec<char>>) {
-    // "crate" is protected word
-    for (mut kasse, stack) in line.chars().chunks(4).into_iter().zip(1u32..) {
-        match kasse.nth(1) {
-            Some(' ') => (),
-            Some(x) => crate_stacks
-                .get_mut(&stack)
-                .expect("Stack index should exist")
-                .push(x),
-            None => panic!("There should be a character here"),
-        }
-    }
+    Ok((crate_stacks, instructions))
 }
 
 fn parse_instruction_line(line: String) -> MoveInstructions {
     let mut line = line.split(' ');
-    let amount: u32 = line
+    let amount: usize = line
         .nth(1)
         .expect("char should be here")
         .parse()
         .expect("should be a number");
-    let from: u32 = line
+    let from: usize = line
         .nth(1)
         .expect("char should be here")
         .parse()
         .expect("should be a number");
-    let to: u32 = line
+    let to: usize = line
         .nth(1)
         .expect("char should be here")
         .parse()
@@ -133,12 +153,6 @@ fn parse_instruction_line(line: String) -> MoveInstructions {
     MoveInstructions { amount, from, to }
 }
 
-fn initialize_stacks(line: String) -> HashMap<u32, Vec<char>> {
-    line.split_whitespace()
-        .map(|num| (num.parse().unwrap(), Vec::new()))
-        .collect()
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -146,41 +160,71 @@ mod tests {
     #[test]
     fn small_sample() {
         let input = read_input("test-input.txt").unwrap();
-        let (mut stacks, instructions) = parse_input(input);
+        let (mut stacks, instructions) = parse_input(input).unwrap();
         dbg!(&instructions);
         for instruction in &instructions {
-            instruction.do_instruction(&mut stacks);
-            println!("Stacks after instruction: {:?}", &stacks)
+            stacks.apply(instruction, CraneModel::CrateMover9000).unwrap();
+            println!("Stacks after instruction:\n{}", &stacks)
         }
         // dbg!(&stacks);
-        assert_eq!("CMZ", get_answer(stacks))
+        assert_eq!("CMZ", stacks.top_crates())
     }
 
     #[test]
-    fn one_line() {
-        let mut crate_stacks: HashMap<u32, Vec<char>> =
-            HashMap::from([(1, Vec::new()), (2, Vec::new()), (3, Vec::new())]);
-        println!(
-            "{:?}, {:?}, {:?}",
-            crate_stacks[&1], crate_stacks[&2], crate_stacks[&3]
+    fn small_sample_both_cranes() {
+        let input = read_input("test-input.txt").unwrap();
+        let (stacks, instructions) = parse_input(input).unwrap();
+        assert_eq!(
+            "CMZ",
+            simulate(stacks.clone(), &instructions, CraneModel::CrateMover9000).unwrap()
         );
-        parse_stack_line("[Z] [M]    ".to_owned(), &mut crate_stacks);
-        dbg!(crate_stacks);
-        panic!();
+        assert_eq!(
+            "MCD",
+            simulate(stacks, &instructions, CraneModel::CrateMover9001).unwrap()
+        );
+    }
+
+    #[test]
+    fn crane_order() {
+        let mut stacks = Stacks::new(2);
+        stacks[1].extend(["A", "B", "C"].map(String::from));
+        let instruction = MoveInstructions {amount: 2, from: 1, to: 2};
+        stacks.apply(&instruction, CraneModel::CrateMover9000).unwrap();
+        assert_eq!(stacks[2], vec!["C", "B"]);
+        let instruction = MoveInstructions {amount: 2, from: 2, to: 1};
+        stacks.apply(&instruction, CraneModel::CrateMover9001).unwrap();
+        assert_eq!(stacks[1], vec!["A", "C", "B"]);
     }
 
     #[test]
-    fn simple_stack() {
-        let input = " 1   2   3 ".to_owned();
-        let output: HashMap<u32, Vec<char>> =
-            HashMap::from([(1, Vec::new()), (2, Vec::new()), (3, Vec::new())]);
-        assert_eq!(initialize_stacks(input), output);
+    fn invalid_moves() {
+        let mut stacks = Stacks::new(2);
+        stacks[1].extend(["A", "B"].map(String::from));
+        let too_many = MoveInstructions {amount: 3, from: 1, to: 2};
+        assert!(stacks.apply(&too_many, CraneModel::CrateMover9000).is_err());
+        let no_stack = MoveInstructions {amount: 1, from: 3, to: 1};
+        assert!(stacks.apply(&no_stack, CraneModel::CrateMover9000).is_err());
+        let no_target = MoveInstructions {amount: 2, from: 1, to: 0};
+        assert!(stacks.apply(&no_target, CraneModel::CrateMover9000).is_err());
+        // Failed moves should not lose any crates
+        assert_eq!(stacks[1], vec!["A", "B"]);
+        assert_eq!(stacks[2], Vec::<String>::new());
+    }
+
+    #[test]
+    fn draw_stacks() {
+        let input = read_input("test-input.txt").unwrap();
+        let drawing = input[..4].join("\n");
+        let (stacks, _) = parse_input(input).unwrap();
+        assert_eq!(stacks.to_string(), drawing);
+        assert_eq!(stacks.top_crates(), "NDP");
     }
 
     #[test]
     fn simple_instruction() {
         let instruction = "move 2 from 3 to 1".to_owned();
         let target_output = MoveInstructions {amount: 2, from: 3, to: 1};
+        assert_eq!(target_output.to_string(), instruction);
         assert_eq!(parse_instruction_line(instruction), target_output);
     }
 }
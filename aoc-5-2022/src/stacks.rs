@@ -0,0 +1,113 @@
+use anyhow::{anyhow, Result};
+use std::fmt;
+use std::ops::{Index, IndexMut};
+
+use crate::{CraneModel, MoveInstructions};
+
+// The crate stacks, numbered from 1 like in the puzzle. The last crate in each
+// Vec is the top of that stack. Crates are labelled by strings, since the
+// labels don't have to be a single letter.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Stacks(Vec<Vec<String>>);
+
+impl Stacks {
+    pub fn new(count: usize) -> Self {
+        Stacks(vec![Vec::new(); count])
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn get(&self, number: usize) -> Option<&Vec<String>> {
+        self.0.get(number.checked_sub(1)?)
+    }
+
+    pub fn get_mut(&mut self, number: usize) -> Option<&mut Vec<String>> {
+        self.0.get_mut(number.checked_sub(1)?)
+    }
+
+    // Empty stacks have no top crate, so they are skipped
+    pub fn top_crates(&self) -> String {
+        self.0
+            .iter()
+            .filter_map(|stack| stack.last().map(|label| label.as_str()))
+            .collect()
+    }
+
+    // Leaves the stacks untouched if the instruction can't be carried out
+    pub fn apply(&mut self, instruction: &MoveInstructions, crane: CraneModel) -> Result<()> {
+        let stack_from = self
+            .get_mut(instruction.from)
+            .ok_or(anyhow!("No stack to take from: {}", instruction.from))?;
+        if stack_from.len() < instruction.amount {
+            return Err(anyhow!(
+                "Can't move {} crates from stack {}, it only has {}",
+                instruction.amount,
+                instruction.from,
+                stack_from.len()
+            ));
+        }
+        let mut moved_crates = stack_from.split_off(stack_from.len() - instruction.amount);
+        if crane == CraneModel::CrateMover9000 {
+            moved_crates.reverse();
+        }
+        match self.get_mut(instruction.to) {
+            Some(stack_to) => {
+                stack_to.extend(moved_crates);
+                Ok(())
+            }
+            None => {
+                // Put the crates back where they came from
+                if crane == CraneModel::CrateMover9000 {
+                    moved_crates.reverse();
+                }
+                self[instruction.from].extend(moved_crates);
+                Err(anyhow!("No stack to move to: {}", instruction.to))
+            }
+        }
+    }
+}
+
+impl Index<usize> for Stacks {
+    type Output = Vec<String>;
+
+    fn index(&self, number: usize) -> &Self::Output {
+        &self.0[number - 1]
+    }
+}
+
+impl IndexMut<usize> for Stacks {
+    fn index_mut(&mut self, number: usize) -> &mut Self::Output {
+        &mut self.0[number - 1]
+    }
+}
+
+// Draws the stacks the same way the puzzle input does. Every column is made
+// wide enough for the widest label or stack number.
+impl fmt::Display for Stacks {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let width = self
+            .0
+            .iter()
+            .flatten()
+            .map(|label| label.chars().count() + 2)
+            .chain([self.len().to_string().len()])
+            .max()
+            .unwrap_or(0);
+        let height = self.0.iter().map(|stack| stack.len()).max().unwrap_or(0);
+        for level in (0..height).rev() {
+            let line: Vec<String> = self
+                .0
+                .iter()
+                .map(|stack| match stack.get(level) {
+                    Some(kasse) => format!("{:^width$}", format!("[{}]", kasse)),
+                    None => " ".repeat(width),
+                })
+                .collect();
+            writeln!(f, "{}", line.join(" "))?;
+        }
+        let numbers: Vec<String> = (1..=self.len()).map(|n| format!("{:^width$}", n)).collect();
+        write!(f, "{}", numbers.join(" "))
+    }
+}
@@ -0,0 +1,147 @@
+use anyhow::{anyhow, Context, Result};
+use std::io::Write;
+use std::thread;
+use std::time::Duration;
+
+use crate::stacks::Stacks;
+use crate::{CraneModel, MoveInstructions};
+
+// Clears the terminal and moves the cursor to the top left corner
+const CLEAR_SCREEN: &str = "\x1B[2J\x1B[H";
+
+#[derive(Debug, PartialEq)]
+pub struct TraceOptions {
+    pub crane: CraneModel,
+    // Redraw in place, waiting this long between steps
+    pub delay: Option<Duration>,
+    // Steps before this one are applied without being shown
+    pub start_step: usize,
+}
+
+impl Default for TraceOptions {
+    fn default() -> Self {
+        TraceOptions {
+            crane: CraneModel::CrateMover9001,
+            delay: None,
+            start_step: 0,
+        }
+    }
+}
+
+impl TraceOptions {
+    // Accepts "--crane 9000", "--animate <ms>" and "--step <n>", in any order
+    pub fn from_args(args: impl IntoIterator<Item = String>) -> Result<Self> {
+        let mut options = TraceOptions::default();
+        let mut args = args.into_iter();
+        while let Some(flag) = args.next() {
+            let value = args.next().ok_or(anyhow!("Missing value after {}", flag))?;
+            match flag.as_str() {
+                "--crane" => options.crane = CraneModel::try_from(value.as_str())?,
+                "--animate" => {
+                    let millis = value
+                        .parse()
+                        .with_context(|| format!("Invalid delay: {}", value))?;
+                    options.delay = Some(Duration::from_millis(millis));
+                }
+                "--step" => {
+                    options.start_step = value
+                        .parse()
+                        .with_context(|| format!("Invalid step: {}", value))?
+                }
+                x => return Err(anyhow!("Unknown flag: {}", x)),
+            }
+        }
+        Ok(options)
+    }
+}
+
+// Draws the stacks after every instruction, starting from `options.start_step`
+// (step 0 is the starting drawing). Returns the final stacks.
+pub fn trace(
+    mut stacks: Stacks,
+    instructions: &[MoveInstructions],
+    options: &TraceOptions,
+    out: &mut impl Write,
+) -> Result<Stacks> {
+    if options.start_step > instructions.len() {
+        return Err(anyhow!(
+            "Can't jump to step {}, there are only {} instructions",
+            options.start_step,
+            instructions.len()
+        ));
+    }
+    if options.start_step == 0 {
+        draw(&stacks, "Step 0: starting drawing", options, out)?;
+    }
+    for (instruction, step) in instructions.iter().zip(1..) {
+        stacks
+            .apply(instruction, options.crane)
+            .with_context(|| format!("Instruction {}: {:?}", step, instruction))?;
+        if step >= options.start_step {
+            let header = format!("Step {}/{}: {}", step, instructions.len(), instruction);
+            draw(&stacks, &header, options, out)?;
+        }
+    }
+    Ok(stacks)
+}
+
+fn draw(stacks: &Stacks, header: &str, options: &TraceOptions, out: &mut impl Write) -> Result<()> {
+    if options.delay.is_some() {
+        write!(out, "{}", CLEAR_SCREEN)?;
+    }
+    writeln!(out, "{}\n{}\n", header, stacks)?;
+    out.flush()?;
+    if let Some(delay) = options.delay {
+        thread::sleep(delay);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{parse_input, read_input};
+
+    #[test]
+    fn trace_small_sample() -> Result<()> {
+        let (stacks, instructions) = parse_input(read_input("test-input.txt")?)?;
+        let options = TraceOptions {
+            crane: CraneModel::CrateMover9000,
+            ..TraceOptions::default()
+        };
+        let mut out = Vec::new();
+        let stacks = trace(stacks, &instructions, &options, &mut out)?;
+        let out = String::from_utf8(out)?;
+        assert_eq!(stacks.top_crates(), "CMZ");
+        assert!(out.starts_with("Step 0: starting drawing\n    [D]    \n"));
+        assert!(out.contains("Step 1/4: move 1 from 2 to 1\n[D]        \n[N] [C]    \n"));
+        assert!(out.ends_with(&format!("Step 4/4: move 1 from 1 to 2\n{}\n\n", stacks)));
+        Ok(())
+    }
+
+    #[test]
+    fn jump_to_step() -> Result<()> {
+        let (stacks, instructions) = parse_input(read_input("test-input.txt")?)?;
+        let options =
+            TraceOptions::from_args(["--step", "3", "--crane", "9000"].map(String::from))?;
+        let mut out = Vec::new();
+        trace(stacks.clone(), &instructions, &options, &mut out)?;
+        let out = String::from_utf8(out)?;
+        assert!(!out.contains("Step 2/4"));
+        assert!(out.starts_with("Step 3/4: move 2 from 2 to 1\n"));
+
+        let options = TraceOptions::from_args(["--step", "5"].map(String::from))?;
+        assert!(trace(stacks, &instructions, &options, &mut Vec::new()).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn parse_options() -> Result<()> {
+        let options = TraceOptions::from_args(["--animate", "250"].map(String::from))?;
+        assert_eq!(options.delay, Some(Duration::from_millis(250)));
+        assert_eq!(options.crane, CraneModel::CrateMover9001);
+        assert!(TraceOptions::from_args(["--crane", "9002"].map(String::from)).is_err());
+        assert!(TraceOptions::from_args(["--step"].map(String::from)).is_err());
+        Ok(())
+    }
+}
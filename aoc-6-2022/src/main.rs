@@ -1,95 +1,149 @@
-use anyhow::Result;
-// use itertools::Itertools;
-// use std::collections::HashMap;
+use anyhow::{anyhow, Result};
+use std::fs::File;
+
+mod stream;
+use stream::{find_markers_in, MarkerDetector};
+
+mod segments;
+use segments::marker_table;
+
+// Window sizes for start-of-packet and start-of-message markers
+const PACKET_WINDOW: usize = 4;
+const MESSAGE_WINDOW: usize = 14;
 
 fn main() -> Result<()> {
-    run("input.txt").expect("Everything should work");
+    let mut args = std::env::args().skip(1);
+    match args.next().as_deref() {
+        None => run("input.txt")?,
+        Some("segments") => {
+            let input = read_input(args.next().as_deref().unwrap_or("input.txt"))?;
+            print!(
+                "{}",
+                marker_table(&input, &[PACKET_WINDOW, MESSAGE_WINDOW])?
+            );
+        }
+        Some("stream") => match args.next() {
+            Some(file) => stream(File::open(file)?)?,
+            None => stream(std::io::stdin().lock())?,
+        },
+        Some(x) => return Err(anyhow!("Unknown mode: {}", x)),
+    }
     Ok(())
 }
 
 fn run(input_file: &str) -> Result<()> {
     let input = read_input(input_file)?;
-    let index = find_marker(input);
-    println!("{}", index);
+    let input = input.trim_end();
+    let packet = find_marker(input, PACKET_WINDOW)?.ok_or(anyhow!("No start-of-packet marker"))?;
+    println!("Part 1: Start-of-packet marker after {}", packet);
+    let message =
+        find_marker(input, MESSAGE_WINDOW)?.ok_or(anyhow!("No start-of-message marker"))?;
+    println!("Part 2: Start-of-message marker after {}", message);
     Ok(())
 }
 
-fn read_input(input_file: &str) -> Result<String> {
-    Ok(std::fs::read_to_string(input_file)?)
+// Prints each marker as soon as it has been read
+fn stream(reader: impl std::io::Read) -> Result<()> {
+    let markers = find_markers_in(
+        reader,
+        &[PACKET_WINDOW, MESSAGE_WINDOW],
+        |window, offset| println!("Marker of size {} after {}", window, offset),
+    )?;
+    if markers.contains(&None) {
+        println!("The signal ended before all markers were found");
+    }
+    Ok(())
 }
 
-fn find_marker(signal: String) -> usize {
-    let mut marker_index = 14;
-    let mut signal = signal.chars().into_iter();
-    let mut current_chars: Vec<char> = signal.by_ref().take(14).collect();
-
-    for c in signal {
-        if no_duplicates(&current_chars) {
-            break
-        }
-        marker_index += 1;
-        current_chars.remove(0);
-        current_chars.push(c);
-    }
-    marker_index
+fn read_input(input_file: &str) -> Result<String> {
+    Ok(std::fs::read_to_string(input_file)?)
 }
 
-fn no_duplicates(characters: &Vec<char>) -> bool {
-    let charactes_amount = characters.len();
-    for i in 0..charactes_amount {
-        if characters[i+1..].contains(&characters[i]) {
-            return false
+// Returns how many characters have been read when the last `window` of them
+// are all different, or None if that never happens
+fn find_marker(signal: &str, window: usize) -> Result<Option<usize>> {
+    let mut detector = MarkerDetector::new(window);
+    for byte in signal.bytes() {
+        if let Some(offset) = detector.push(byte)? {
+            return Ok(Some(offset));
         }
     }
-    true
+    Ok(None)
 }
 
-
 #[cfg(test)]
 mod tests {
     use super::*;
 
     #[test]
-    fn duplicate_check() {
-        let input1: Vec<char> = "abca".chars().collect();
-        let input2: Vec<char>= "abcd".chars().collect();
+    fn duplicate_check() -> Result<()> {
+        assert_eq!(find_marker("abcad", 4)?, Some(5));
+        assert_eq!(find_marker("abcd", 4)?, Some(4));
+        assert_eq!(find_marker("abcab", 3)?, Some(3));
+        assert_eq!(find_marker("aabcd", 4)?, Some(5));
+        Ok(())
+    }
 
-        assert_eq!(no_duplicates(&input1), false);
-        assert_eq!(no_duplicates(&input2), true);
+    #[test]
+    fn short_signal() -> Result<()> {
+        assert_eq!(find_marker("", 4)?, None);
+        assert_eq!(find_marker("abc", 4)?, None);
+        assert_eq!(find_marker("abcdefghijklm", MESSAGE_WINDOW)?, None);
+        assert_eq!(find_marker("abcd", 4)?, Some(4));
+        Ok(())
+    }
+
+    #[test]
+    fn no_marker() -> Result<()> {
+        assert_eq!(find_marker("abcabcabcabc", 4)?, None);
+        assert_eq!(find_marker("aaaaaaaaaaaaaaaaaaaa", 2)?, None);
+        // The last window is checked too
+        assert_eq!(find_marker("aaaaaaab", 2)?, Some(8));
+        Ok(())
+    }
+
+    #[test]
+    fn non_ascii() {
+        assert!(find_marker("abé", 4).is_err());
     }
 
     #[test]
-    fn test1() {
-        let input = read_input("test1.txt").expect("didn't work");
-        let index = find_marker(input);
-        assert_eq!(index, 19);
+    fn test1() -> Result<()> {
+        let input = read_input("test1.txt")?;
+        assert_eq!(find_marker(&input, PACKET_WINDOW)?, Some(7));
+        assert_eq!(find_marker(&input, MESSAGE_WINDOW)?, Some(19));
+        Ok(())
     }
 
     #[test]
-    fn test2() {
-        let input = read_input("test2.txt").expect("didn't work");
-        let index = find_marker(input);
-        assert_eq!(index, 23);
+    fn test2() -> Result<()> {
+        let input = read_input("test2.txt")?;
+        assert_eq!(find_marker(&input, PACKET_WINDOW)?, Some(5));
+        assert_eq!(find_marker(&input, MESSAGE_WINDOW)?, Some(23));
+        Ok(())
     }
 
     #[test]
-    fn test3() {
-        let input = read_input("test3.txt").expect("didn't work");
-        let index = find_marker(input);
-        assert_eq!(index, 23);
+    fn test3() -> Result<()> {
+        let input = read_input("test3.txt")?;
+        assert_eq!(find_marker(&input, PACKET_WINDOW)?, Some(6));
+        assert_eq!(find_marker(&input, MESSAGE_WINDOW)?, Some(23));
+        Ok(())
     }
 
     #[test]
-    fn test4() {
-        let input = read_input("test4.txt").expect("didn't work");
-        let index = find_marker(input);
-        assert_eq!(index, 29);
+    fn test4() -> Result<()> {
+        let input = read_input("test4.txt")?;
+        assert_eq!(find_marker(&input, PACKET_WINDOW)?, Some(10));
+        assert_eq!(find_marker(&input, MESSAGE_WINDOW)?, Some(29));
+        Ok(())
     }
 
     #[test]
-    fn test5() {
-        let input = read_input("test5.txt").expect("didn't work");
-        let index = find_marker(input);
-        assert_eq!(index, 26);
+    fn test5() -> Result<()> {
+        let input = read_input("test5.txt")?;
+        assert_eq!(find_marker(&input, PACKET_WINDOW)?, Some(11));
+        assert_eq!(find_marker(&input, MESSAGE_WINDOW)?, Some(26));
+        Ok(())
     }
 }
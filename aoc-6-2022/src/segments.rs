@@ -0,0 +1,117 @@
+use anyhow::Result;
+
+use crate::stream::MarkerDetector;
+
+// Longest part of a segment shown in the table
+const PREVIEW: usize = 20;
+
+#[derive(Debug, PartialEq)]
+pub struct Marker {
+    pub window: usize,
+    // Where the marker starts, and how many bytes have been read when it ends
+    pub start: usize,
+    pub end: usize,
+}
+
+// Finds every marker of the given size, left to right. Markers don't overlap:
+// after one is found, the search for the next one starts right after it.
+pub fn find_all_markers(signal: &str, window: usize) -> Result<Vec<Marker>> {
+    let mut markers = Vec::new();
+    let mut detector = MarkerDetector::new(window);
+    let mut start = 0;
+    for (offset, &byte) in signal.as_bytes().iter().enumerate() {
+        if let Some(length) = detector.push(byte)? {
+            markers.push(Marker {
+                window,
+                start: start + length - window,
+                end: start + length,
+            });
+            start = offset + 1;
+            detector = MarkerDetector::new(window);
+        }
+    }
+    Ok(markers)
+}
+
+// The data after each marker, up to where the next one starts (or to the end
+// of the signal for the last one). Markers have to be sorted and not overlap.
+pub fn segments<'a>(signal: &'a str, markers: &[Marker]) -> Vec<&'a str> {
+    markers
+        .iter()
+        .enumerate()
+        .map(|(i, marker)| {
+            let next = markers.get(i + 1).map_or(signal.len(), |m| m.start);
+            &signal[marker.end..next]
+        })
+        .collect()
+}
+
+// Lists the markers of each size with the segments they start
+pub fn marker_table(signal: &str, windows: &[usize]) -> Result<String> {
+    let signal = signal.trim_end();
+    let mut table = format!(
+        "{:>6} {:>8} {:>8} {:>8}  {}\n",
+        "Window", "Start", "End", "Length", "Segment"
+    );
+    for &window in windows {
+        let markers = find_all_markers(signal, window)?;
+        for (marker, segment) in markers.iter().zip(segments(signal, &markers)) {
+            let preview = if segment.len() > PREVIEW {
+                format!("{}...", &segment[..PREVIEW])
+            } else {
+                segment.to_owned()
+            };
+            table.push_str(&format!(
+                "{:>6} {:>8} {:>8} {:>8}  {}\n",
+                marker.window,
+                marker.start,
+                marker.end,
+                segment.len(),
+                preview
+            ));
+        }
+    }
+    Ok(table)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn all_markers() -> Result<()> {
+        let markers = find_all_markers("abcdaaaxyzwbbqrst", 4)?;
+        let ends: Vec<usize> = markers.iter().map(|m| m.end).collect();
+        assert_eq!(ends, vec![4, 10, 16]);
+        assert_eq!(markers[1].start, 6);
+        assert_eq!(
+            segments("abcdaaaxyzwbbqrst", &markers),
+            vec!["aa", "wb", "t"]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn first_marker_matches_sample() -> Result<()> {
+        let signal = "mjqjpqmgbljsphdztnvjfqwrcgsmlb";
+        assert_eq!(find_all_markers(signal, 4)?[0].end, 7);
+        assert_eq!(find_all_markers(signal, 14)?[0].end, 19);
+        Ok(())
+    }
+
+    #[test]
+    fn no_markers() -> Result<()> {
+        assert_eq!(find_all_markers("aaaa", 2)?, Vec::new());
+        assert_eq!(segments("aaaa", &[]), Vec::<&str>::new());
+        Ok(())
+    }
+
+    #[test]
+    fn table() -> Result<()> {
+        let table = marker_table("abcdaaaxyzwbbqrst\n", &[4])?;
+        let lines: Vec<&str> = table.lines().collect();
+        assert_eq!(lines.len(), 4);
+        assert_eq!(lines[2], "     4        6       10        2  wb");
+        Ok(())
+    }
+}
@@ -0,0 +1,151 @@
+use anyhow::{anyhow, Result};
+use std::collections::VecDeque;
+use std::io::{BufReader, Read};
+
+// Looks for a marker one byte at a time, so the signal never has to be in
+// memory all at once. Only ASCII is accepted: the signal is letters, and
+// counting UTF-8 bytes as characters would give the wrong offsets.
+#[derive(Debug)]
+pub struct MarkerDetector {
+    window: usize,
+    recent: VecDeque<u8>,
+    counts: [usize; 128],
+    // How many different bytes are in the window more than once
+    repeated: usize,
+    // Bytes read so far
+    offset: usize,
+}
+
+impl MarkerDetector {
+    pub fn new(window: usize) -> Self {
+        MarkerDetector {
+            window,
+            recent: VecDeque::with_capacity(window + 1),
+            counts: [0; 128],
+            repeated: 0,
+            offset: 0,
+        }
+    }
+
+    // Reads one more byte, and returns how many bytes have been read if they
+    // end with a marker
+    pub fn push(&mut self, byte: u8) -> Result<Option<usize>> {
+        if !byte.is_ascii() {
+            return Err(anyhow!(
+                "Non-ASCII byte {:#04x} at offset {}",
+                byte,
+                self.offset
+            ));
+        }
+        self.offset += 1;
+        self.recent.push_back(byte);
+        self.counts[byte as usize] += 1;
+        if self.counts[byte as usize] == 2 {
+            self.repeated += 1;
+        }
+        if self.recent.len() > self.window {
+            let old = self.recent.pop_front().expect("Window is not empty") as usize;
+            self.counts[old] -= 1;
+            if self.counts[old] == 1 {
+                self.repeated -= 1;
+            }
+        }
+        if self.recent.len() == self.window && self.repeated == 0 {
+            Ok(Some(self.offset))
+        } else {
+            Ok(None)
+        }
+    }
+}
+
+// Looks for the first marker of each window size in a single pass over
+// `reader`, calling `found(window, offset)` as soon as each one turns up, and
+// stops reading once all of them have. A trailing newline ends the signal.
+pub fn find_markers_in(
+    reader: impl Read,
+    windows: &[usize],
+    mut found: impl FnMut(usize, usize),
+) -> Result<Vec<Option<usize>>> {
+    let mut detectors: Vec<MarkerDetector> =
+        windows.iter().map(|&w| MarkerDetector::new(w)).collect();
+    let mut markers = vec![None; windows.len()];
+    for byte in BufReader::new(reader).bytes() {
+        let byte = byte?;
+        if byte == b'\n' {
+            break;
+        }
+        for (detector, marker) in detectors.iter_mut().zip(markers.iter_mut()) {
+            if marker.is_none() {
+                *marker = detector.push(byte)?;
+                if let Some(offset) = *marker {
+                    found(detector.window, offset);
+                }
+            }
+        }
+        if markers.iter().all(|m| m.is_some()) {
+            break;
+        }
+    }
+    Ok(markers)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs::File;
+
+    #[test]
+    fn small_samples() -> Result<()> {
+        let expected = [
+            ("test1.txt", 7, 19),
+            ("test2.txt", 5, 23),
+            ("test3.txt", 6, 23),
+            ("test4.txt", 10, 29),
+            ("test5.txt", 11, 26),
+        ];
+        for (file, packet, message) in expected {
+            let markers = find_markers_in(File::open(file)?, &[4, 14], |_, _| ())?;
+            assert_eq!(markers, vec![Some(packet), Some(message)]);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn reports_as_found() -> Result<()> {
+        let mut seen = Vec::new();
+        find_markers_in(
+            "mjqjpqmgbljsphdztnvjfqwrcgsmlb".as_bytes(),
+            &[14, 4],
+            |w, o| seen.push((w, o)),
+        )?;
+        assert_eq!(seen, vec![(4, 7), (14, 19)]);
+        Ok(())
+    }
+
+    #[test]
+    fn stops_reading_after_markers() -> Result<()> {
+        // The invalid byte after the markers is never looked at
+        let signal = b"abcd\xff";
+        assert_eq!(
+            find_markers_in(&signal[..], &[4], |_, _| ())?,
+            vec![Some(4)]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn no_marker() -> Result<()> {
+        assert_eq!(
+            find_markers_in("aab\n".as_bytes(), &[3], |_, _| ())?,
+            vec![None]
+        );
+        assert_eq!(find_markers_in("".as_bytes(), &[1], |_, _| ())?, vec![None]);
+        Ok(())
+    }
+
+    #[test]
+    fn non_ascii() {
+        let error = find_markers_in("aaé".as_bytes(), &[4], |_, _| ()).unwrap_err();
+        assert_eq!(error.to_string(), "Non-ASCII byte 0xc3 at offset 2");
+    }
+}
@@ -0,0 +1,230 @@
+use anyhow::{anyhow, Result};
+use std::io::{BufRead, Write};
+
+use crate::filesystem::{FileSystem, NodeId};
+use crate::report::{size_table, sort_by_size};
+use crate::transcript::Entry;
+
+// Looks around a filesystem that has been built from a transcript, with a few
+// shell commands. Nothing can be changed, only looked at.
+#[derive(Debug)]
+pub struct Explorer<'a> {
+    filesystem: &'a FileSystem,
+    current: NodeId,
+}
+
+// What `find -size` compares against
+#[derive(Debug, PartialEq)]
+enum SizeFilter {
+    MoreThan(usize),
+    LessThan(usize),
+    Exactly(usize),
+}
+
+impl TryFrom<&str> for SizeFilter {
+    type Error = anyhow::Error;
+
+    // "+N", "-N" or "N", like in `find`
+    fn try_from(value: &str) -> Result<Self> {
+        if let Some(size) = value.strip_prefix('+') {
+            Ok(SizeFilter::MoreThan(size.parse()?))
+        } else if let Some(size) = value.strip_prefix('-') {
+            Ok(SizeFilter::LessThan(size.parse()?))
+        } else {
+            Ok(SizeFilter::Exactly(value.parse()?))
+        }
+    }
+}
+
+impl SizeFilter {
+    fn matches(&self, size: usize) -> bool {
+        match *self {
+            SizeFilter::MoreThan(n) => size > n,
+            SizeFilter::LessThan(n) => size < n,
+            SizeFilter::Exactly(n) => size == n,
+        }
+    }
+}
+
+impl<'a> Explorer<'a> {
+    pub fn new(filesystem: &'a FileSystem) -> Self {
+        Explorer {
+            filesystem,
+            current: filesystem.root(),
+        }
+    }
+
+    // Runs one command per line, with a prompt showing where we are. Errors
+    // are printed, and don't end the session.
+    pub fn run_commands(&mut self, input: impl BufRead, out: &mut impl Write) -> Result<()> {
+        write!(out, "{}$ ", self.filesystem.path(self.current))?;
+        out.flush()?;
+        for line in input.lines() {
+            let line = line?;
+            if let Err(error) = self.run_command(&line, out) {
+                writeln!(out, "Error: {:#}", error)?;
+            }
+            write!(out, "{}$ ", self.filesystem.path(self.current))?;
+            out.flush()?;
+        }
+        writeln!(out)?;
+        Ok(())
+    }
+
+    // Commands can be written like in a transcript, starting with "$"
+    fn run_command(&mut self, line: &str, out: &mut impl Write) -> Result<()> {
+        let words: Vec<&str> = line.split_whitespace().collect();
+        let words = match words.as_slice() {
+            ["$", rest @ ..] => rest,
+            words => words,
+        };
+        match words {
+            [] => (),
+            ["pwd"] => writeln!(out, "{}", self.filesystem.path(self.current))?,
+            ["cd"] => self.current = self.filesystem.root(),
+            ["cd", path] => {
+                let id = self.resolve(path)?;
+                if !self.filesystem.node(id).is_directory() {
+                    return Err(anyhow!("Not a directory: {}", path));
+                }
+                self.current = id;
+            }
+            ["ls"] => self.ls(self.current, out)?,
+            ["ls", path] => self.ls(self.resolve(path)?, out)?,
+            ["du"] => self.du(self.current, out)?,
+            ["du", path] => self.du(self.resolve(path)?, out)?,
+            ["find", "-size", size] => self.find(SizeFilter::try_from(*size)?, out)?,
+            _ => return Err(anyhow!("Unknown command: {}", line.trim())),
+        }
+        Ok(())
+    }
+
+    // Paths can be absolute or relative, and can use ".." and "."
+    fn resolve(&self, path: &str) -> Result<NodeId> {
+        let mut id = if path.starts_with('/') {
+            self.filesystem.root()
+        } else {
+            self.current
+        };
+        for name in path.split('/').filter(|name| !name.is_empty()) {
+            id = match name {
+                "." => id,
+                ".." => self.filesystem.node(id).parent.unwrap_or(id),
+                name => self
+                    .filesystem
+                    .child(id, name)
+                    .ok_or(anyhow!("No such file or directory: {}", path))?,
+            };
+        }
+        Ok(id)
+    }
+
+    // Shows the directory like the `ls` in the transcript did
+    fn ls(&self, id: NodeId, out: &mut impl Write) -> Result<()> {
+        let ids = match self.filesystem.node(id).children() {
+            [] if !self.filesystem.node(id).is_directory() => vec![id],
+            children => children.to_vec(),
+        };
+        for id in ids {
+            writeln!(out, "{}", Entry::of(self.filesystem, id))?;
+        }
+        Ok(())
+    }
+
+    // Every directory in `id`, and itself, largest first
+    fn du(&self, id: NodeId, out: &mut impl Write) -> Result<()> {
+        let mut directories: Vec<NodeId> = (self.filesystem.descendants(id).into_iter())
+            .filter(|&id| self.filesystem.node(id).is_directory())
+            .collect();
+        sort_by_size(self.filesystem, &mut directories);
+        write!(out, "{}", size_table(self.filesystem, &directories))?;
+        Ok(())
+    }
+
+    // Files and directories below the current one with a matching size,
+    // largest first
+    fn find(&self, filter: SizeFilter, out: &mut impl Write) -> Result<()> {
+        let mut found: Vec<NodeId> = (self.filesystem.descendants(self.current).into_iter())
+            .filter(|&id| filter.matches(self.filesystem.size(id)))
+            .collect();
+        sort_by_size(self.filesystem, &mut found);
+        write!(out, "{}", size_table(self.filesystem, &found))?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{build_filesystem, read_input};
+
+    fn session(commands: &str) -> Result<String> {
+        let filesystem = build_filesystem(read_input("test.txt")?)?;
+        let mut out = Vec::new();
+        Explorer::new(&filesystem).run_commands(commands.as_bytes(), &mut out)?;
+        Ok(String::from_utf8(out)?)
+    }
+
+    #[test]
+    fn moving_around() -> Result<()> {
+        let out = session("cd a/e\npwd\ncd ..\n$ cd /d\ncd /a/../d/./\npwd\ncd\npwd")?;
+        assert_eq!(
+            out,
+            "/$ /a/e/$ /a/e/\n/a/e/$ /a/$ /d/$ /d/$ /d/\n/d/$ /$ /\n/$ \n"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn listing() -> Result<()> {
+        let out = session("ls\nls a/f")?;
+        assert_eq!(
+            out,
+            "/$ dir a\n14848514 b.txt\n8504156 c.dat\ndir d\n/$ 29116 f\n/$ \n"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn sizes() -> Result<()> {
+        let out = session("cd a\ndu\nfind -size +20000\nfind -size -600")?;
+        let lines: Vec<&str> = out.lines().collect();
+        assert_eq!(
+            lines,
+            vec![
+                "/$ /a/$      94853  /a/",
+                "       584  /a/e/",
+                "/a/$      94853  /a/",
+                "     62596  /a/h.lst",
+                "     29116  /a/f",
+                "/a/$        584  /a/e/",
+                "       584  /a/e/i",
+                "/a/$ ",
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn errors() -> Result<()> {
+        let out = session("cd b.txt\ncd nowhere\ncd ../..\nfly\nfind -size +x\npwd")?;
+        assert!(out.contains("Error: Not a directory: b.txt\n"));
+        assert!(out.contains("Error: No such file or directory: nowhere\n"));
+        assert!(out.contains("Error: Unknown command: fly\n"));
+        assert!(out.contains("Error: invalid digit"));
+        // Going above the root stays there, like in a shell
+        assert!(out.ends_with("/$ /\n/$ \n"));
+        Ok(())
+    }
+
+    #[test]
+    fn size_filters() -> Result<()> {
+        assert_eq!(SizeFilter::try_from("+10")?, SizeFilter::MoreThan(10));
+        assert_eq!(SizeFilter::try_from("-10")?, SizeFilter::LessThan(10));
+        assert_eq!(SizeFilter::try_from("10")?, SizeFilter::Exactly(10));
+        assert!(SizeFilter::try_from("").is_err());
+        assert!(SizeFilter::try_from("é5").is_err());
+        assert!(SizeFilter::try_from("+é").is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,218 @@
+// The filesystem is kept as an arena: every file and directory lives in one
+// Vec, and they refer to each other by their index in it. A node is always
+// added after its parent, so every child has a larger id than its parent.
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct NodeId(usize);
+
+#[derive(Debug)]
+pub enum NodeKind {
+    Directory { children: Vec<NodeId> },
+    File,
+}
+
+#[derive(Debug)]
+pub struct Node {
+    pub name: String,
+    pub parent: Option<NodeId>,
+    pub kind: NodeKind,
+    // For directories, the total size of everything in them. Only up to date
+    // after `compute_sizes`.
+    size: usize,
+}
+
+impl Node {
+    pub fn is_directory(&self) -> bool {
+        matches!(self.kind, NodeKind::Directory { .. })
+    }
+
+    pub fn children(&self) -> &[NodeId] {
+        match &self.kind {
+            NodeKind::Directory { children } => children,
+            NodeKind::File => &[],
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct FileSystem {
+    nodes: Vec<Node>,
+}
+
+impl Default for FileSystem {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl FileSystem {
+    // A filesystem with only the root directory, "/"
+    pub fn new() -> Self {
+        FileSystem {
+            nodes: vec![Node {
+                name: String::from("/"),
+                parent: None,
+                kind: NodeKind::Directory {
+                    children: Vec::new(),
+                },
+                size: 0,
+            }],
+        }
+    }
+
+    pub fn root(&self) -> NodeId {
+        NodeId(0)
+    }
+
+    pub fn node(&self, id: NodeId) -> &Node {
+        &self.nodes[id.0]
+    }
+
+    pub fn ids(&self) -> impl Iterator<Item = NodeId> {
+        (0..self.nodes.len()).map(NodeId)
+    }
+
+    pub fn directories(&self) -> impl Iterator<Item = NodeId> + '_ {
+        self.ids().filter(|&id| self.node(id).is_directory())
+    }
+
+    pub fn files(&self) -> impl Iterator<Item = NodeId> + '_ {
+        self.ids().filter(|&id| !self.node(id).is_directory())
+    }
+
+    // The node and everything below it, depth-first
+    pub fn descendants(&self, id: NodeId) -> Vec<NodeId> {
+        let mut found = Vec::new();
+        let mut stack = vec![id];
+        while let Some(id) = stack.pop() {
+            found.push(id);
+            stack.extend(self.node(id).children().iter().rev());
+        }
+        found
+    }
+
+    pub fn child(&self, directory: NodeId, name: &str) -> Option<NodeId> {
+        self.node(directory)
+            .children()
+            .iter()
+            .copied()
+            .find(|&id| self.node(id).name == name)
+    }
+
+    // Returns the existing directory if there already is one with that name
+    pub fn add_directory(&mut self, parent: NodeId, name: &str) -> NodeId {
+        if let Some(id) = self.child(parent, name) {
+            return id;
+        }
+        self.add_node(
+            parent,
+            name,
+            NodeKind::Directory {
+                children: Vec::new(),
+            },
+            0,
+        )
+    }
+
+    pub fn add_file(&mut self, parent: NodeId, name: &str, size: usize) -> NodeId {
+        if let Some(id) = self.child(parent, name) {
+            return id;
+        }
+        self.add_node(parent, name, NodeKind::File, size)
+    }
+
+    fn add_node(&mut self, parent: NodeId, name: &str, kind: NodeKind, size: usize) -> NodeId {
+        let id = NodeId(self.nodes.len());
+        match &mut self.nodes[parent.0].kind {
+            NodeKind::Directory { children } => children.push(id),
+            NodeKind::File => panic!("Can't add {} to a file", name),
+        }
+        self.nodes.push(Node {
+            name: name.to_owned(),
+            parent: Some(parent),
+            kind,
+            size,
+        });
+        id
+    }
+
+    // Sums up every directory in one post-order pass. Children always come
+    // after their parent, so going through the nodes backwards handles every
+    // child before its parent.
+    pub fn compute_sizes(&mut self) {
+        for node in self.nodes.iter_mut().filter(|n| n.is_directory()) {
+            node.size = 0;
+        }
+        for i in (1..self.nodes.len()).rev() {
+            let size = self.nodes[i].size;
+            let parent = self.nodes[i].parent.expect("Only the root has no parent");
+            self.nodes[parent.0].size += size;
+        }
+    }
+
+    pub fn size(&self, id: NodeId) -> usize {
+        self.node(id).size
+    }
+
+    // Directories end with "/", like "/a/e/"
+    pub fn path(&self, id: NodeId) -> String {
+        let mut names = Vec::new();
+        let mut current = Some(id);
+        while let Some(id) = current {
+            let node = self.node(id);
+            if node.parent.is_some() {
+                names.push(node.name.as_str());
+            }
+            current = node.parent;
+        }
+        names.reverse();
+        let mut path = format!("/{}", names.join("/"));
+        if self.node(id).is_directory() && id != self.root() {
+            path.push('/');
+        }
+        path
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sizes_and_parents() {
+        let mut fs = FileSystem::new();
+        let root = fs.root();
+        let a = fs.add_directory(root, "a");
+        let e = fs.add_directory(a, "e");
+        let i = fs.add_file(e, "i", 584);
+        fs.add_file(a, "f", 29116);
+        fs.add_file(root, "b.txt", 14848514);
+        fs.compute_sizes();
+
+        assert_eq!(fs.size(e), 584);
+        assert_eq!(fs.size(a), 29700);
+        assert_eq!(fs.size(root), 14878214);
+        assert_eq!(fs.node(i).name, "i");
+        assert_eq!(fs.node(i).parent, Some(e));
+        assert_eq!(fs.directories().count(), 3);
+        assert_eq!(fs.files().count(), 3);
+        assert_eq!(fs.path(root), "/");
+        assert_eq!(fs.path(e), "/a/e/");
+        assert_eq!(fs.path(i), "/a/e/i");
+        assert_eq!(fs.descendants(a).len(), 4);
+        assert_eq!(fs.descendants(a)[1], e);
+    }
+
+    #[test]
+    fn adding_twice() {
+        let mut fs = FileSystem::new();
+        let root = fs.root();
+        let a = fs.add_directory(root, "a");
+        assert_eq!(fs.add_directory(root, "a"), a);
+        fs.add_file(a, "x", 10);
+        fs.add_file(a, "x", 10);
+        fs.compute_sizes();
+        assert_eq!(fs.size(root), 10);
+        assert_eq!(fs.node(a).children().len(), 1);
+    }
+}
@@ -0,0 +1,210 @@
+use anyhow::{anyhow, Context, Result};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+use crate::filesystem::{FileSystem, NodeId};
+use crate::transcript::Entry;
+
+// Reads a filesystem drawn like `report::tree` does it, two spaces of
+// indentation per level:
+//   - / (dir)
+//     - a (dir)
+//       - i (file, size=584)
+// Directory sizes may be given, but are worked out again anyway.
+pub fn parse_tree(lines: &[String]) -> Result<FileSystem> {
+    let mut filesystem = FileSystem::new();
+    // The directory at each depth above the current line
+    let mut parents: Vec<NodeId> = Vec::new();
+    for (line, line_nr) in lines.iter().zip(1..) {
+        if line.trim().is_empty() {
+            continue;
+        }
+        parse_tree_line(line, &mut filesystem, &mut parents)
+            .with_context(|| format!("Line {}: {:?}", line_nr, line))?;
+    }
+    if parents.is_empty() {
+        return Err(anyhow!("No root directory"));
+    }
+    filesystem.compute_sizes();
+    Ok(filesystem)
+}
+
+fn parse_tree_line(
+    line: &str,
+    filesystem: &mut FileSystem,
+    parents: &mut Vec<NodeId>,
+) -> Result<()> {
+    let item = line.trim_start_matches(' ');
+    let indent = line.len() - item.len();
+    if !indent.is_multiple_of(2) {
+        return Err(anyhow!("Indentation should be two spaces per level"));
+    }
+    let depth = indent / 2;
+    let (name, kind) = item
+        .strip_prefix("- ")
+        .and_then(|item| item.strip_suffix(')'))
+        .and_then(|item| item.rsplit_once(" ("))
+        .ok_or(anyhow!("Expected \"- <name> (<dir or file>)\""))?;
+    let mut details = kind.split(", ");
+    let is_directory = match details.next() {
+        Some("dir") => true,
+        Some("file") => false,
+        _ => return Err(anyhow!("Neither a dir nor a file: {}", kind)),
+    };
+    let size = details.find_map(|detail| detail.strip_prefix("size="));
+
+    if depth == 0 {
+        if !parents.is_empty() || name != "/" || !is_directory {
+            return Err(anyhow!("Only / can be at the top"));
+        }
+        parents.push(filesystem.root());
+        return Ok(());
+    }
+    // Anything else would come out of `transcript` as a different command
+    if name.is_empty()
+        || name.contains(|c: char| c.is_whitespace() || c == '/')
+        || name == "."
+        || name == ".."
+    {
+        return Err(anyhow!("Not a valid name: {:?}", name));
+    }
+    if depth > parents.len() {
+        return Err(anyhow!("Indented too far"));
+    }
+    parents.truncate(depth);
+    let parent = parents[depth - 1];
+    if filesystem.child(parent, name).is_some() {
+        return Err(anyhow!("{} is in {} twice", name, filesystem.path(parent)));
+    }
+    if is_directory {
+        parents.push(filesystem.add_directory(parent, name));
+    } else {
+        let size = size
+            .ok_or(anyhow!("A file needs a size"))?
+            .parse()
+            .context("Invalid size")?;
+        filesystem.add_file(parent, name, size);
+    }
+    Ok(())
+}
+
+// A terminal session that explores the whole filesystem: every directory is
+// listed once, and then each directory in it is gone into and back out of.
+// Empty directories are listed too, so reading it back gives the same tree.
+pub fn transcript(filesystem: &FileSystem) -> Vec<String> {
+    let mut lines = vec![String::from("$ cd /")];
+    emit_directory(filesystem, filesystem.root(), &mut lines);
+    lines
+}
+
+fn emit_directory(filesystem: &FileSystem, id: NodeId, lines: &mut Vec<String>) {
+    let children = filesystem.node(id).children();
+    lines.push(String::from("$ ls"));
+    lines.extend(
+        children
+            .iter()
+            .map(|&child| Entry::of(filesystem, child).to_string()),
+    );
+    for &child in children {
+        let node = filesystem.node(child);
+        if node.is_directory() {
+            lines.push(format!("$ cd {}", node.name));
+            emit_directory(filesystem, child, lines);
+            lines.push(String::from("$ cd .."));
+        }
+    }
+}
+
+// A made-up filesystem, for testing and for making large inputs. Every
+// directory or file goes into a random directory made before it.
+pub fn random_filesystem(directories: usize, files: usize, seed: u64) -> FileSystem {
+    let mut rng = StdRng::seed_from_u64(seed);
+    let mut filesystem = FileSystem::new();
+    let mut made = vec![filesystem.root()];
+    for i in 0..directories {
+        let parent = made[rng.gen_range(0..made.len())];
+        made.push(filesystem.add_directory(parent, &format!("d{}", i)));
+    }
+    for i in 0..files {
+        let parent = made[rng.gen_range(0..made.len())];
+        let extension = ["", ".txt", ".dat", ".log"][rng.gen_range(0..4)];
+        let size = rng.gen_range(1..=300_000);
+        filesystem.add_file(parent, &format!("f{}{}", i, extension), size);
+    }
+    filesystem.compute_sizes();
+    filesystem
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::report::tree;
+    use crate::transcript::Interpreter;
+    use crate::{build_filesystem, read_input};
+
+    fn lines(text: &str) -> Vec<String> {
+        text.lines().map(String::from).collect()
+    }
+
+    #[test]
+    fn small_sample_round_trip() -> Result<()> {
+        let filesystem = build_filesystem(read_input("test.txt")?)?;
+        let drawing = tree(&filesystem);
+        let parsed = parse_tree(&lines(&drawing))?;
+        assert_eq!(tree(&parsed), drawing);
+        let (replayed, warnings) = Interpreter::new().run(&transcript(&parsed))?;
+        assert_eq!(warnings, Vec::new());
+        assert_eq!(tree(&replayed), drawing);
+        Ok(())
+    }
+
+    #[test]
+    fn emitted_transcript() -> Result<()> {
+        let filesystem = parse_tree(&lines(
+            "- / (dir)\n  - a (dir)\n    - b (dir)\n  - x (file, size=5)",
+        ))?;
+        assert_eq!(
+            transcript(&filesystem),
+            lines("$ cd /\n$ ls\ndir a\n5 x\n$ cd a\n$ ls\ndir b\n$ cd b\n$ ls\n$ cd ..\n$ cd ..")
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn random_round_trips() -> Result<()> {
+        for seed in 0..50 {
+            let filesystem = random_filesystem(seed as usize % 20, 30, seed);
+            let (replayed, warnings) = Interpreter::new().run(&transcript(&filesystem))?;
+            assert_eq!(warnings, Vec::new());
+            assert_eq!(tree(&replayed), tree(&filesystem), "Seed {}", seed);
+            let drawing = tree(&filesystem);
+            assert_eq!(tree(&parse_tree(&lines(&drawing))?), drawing);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn bad_trees() {
+        let parse = |text: &str| parse_tree(&lines(text)).unwrap_err().to_string();
+        assert_eq!(parse(""), "No root directory");
+        assert_eq!(parse("- a (dir)"), "Line 1: \"- a (dir)\"");
+        assert!(parse_tree(&lines("- / (dir)\n    - a (dir)")).is_err());
+        assert!(parse_tree(&lines("- / (dir)\n  - a (file)")).is_err());
+        assert!(parse_tree(&lines("- / (dir)\n  - a (dir)\n  - a (dir)")).is_err());
+        assert!(parse_tree(&lines("- / (dir)\n - a (dir)")).is_err());
+    }
+
+    #[test]
+    fn bad_names() {
+        let error = |name: &str| {
+            let text = format!("- / (dir)\n  - {} (dir)", name);
+            format!("{:#}", parse_tree(&lines(&text)).unwrap_err())
+        };
+        assert!(error("my dir").ends_with("Not a valid name: \"my dir\""));
+        assert!(error("a\tb").ends_with("Not a valid name: \"a\\tb\""));
+        assert!(error("a/b").ends_with("Not a valid name: \"a/b\""));
+        assert!(error(".").ends_with("Not a valid name: \".\""));
+        assert!(error("..").ends_with("Not a valid name: \"..\""));
+        assert!(parse_tree(&lines("- / (dir)\n  - .hidden (file, size=1)")).is_ok());
+    }
+}
@@ -1,104 +1,177 @@
-use anyhow::Result;
-use itertools::Itertools;
-use std::collections::HashMap;
+use anyhow::{anyhow, Context, Result};
 use std::io::BufRead;
 
-enum Command {
-    Cd,
-    Ls,
-    Output,
-}
+mod filesystem;
+use filesystem::{FileSystem, NodeId};
 
-impl Command {
-    fn is_command(&self) -> bool {
-        match self {
-            Command::Output => false,
-            _ => true,
-        }
-    }
+mod explorer;
+use explorer::Explorer;
 
-    fn is_output(&self) -> bool {
-        !self.is_command()
-    }
-}
+mod generator;
+
+mod report;
+
+mod transcript;
+use transcript::Interpreter;
 
-#[derive(Debug)]
-struct Directory {
-    path: String, //put in whole path here, makes things easier, ie. /d/ (end with /)
-    sub_directories: Vec<String>, //only name of subdir
-    files: Vec<usize>, //just input the size of the files for now
+#[derive(Debug, PartialEq)]
+struct DiskConfig {
+    // Total size of the disk
+    capacity: usize,
+    // Free space needed for the update
+    required: usize,
+    // Largest directory counted in part 1
+    threshold: usize,
 }
 
-impl Directory {
-    fn sub_directory_paths(&self) -> Vec<String> {
-        self.sub_directories
-            .iter()
-            .map(|d| format!("{}{}/", self.path, d))
-            .collect()
+impl Default for DiskConfig {
+    fn default() -> Self {
+        DiskConfig {
+            capacity: 70_000_000,
+            required: 30_000_000,
+            threshold: 100_000,
+        }
     }
+}
 
-    fn files_size(&self) -> usize {
-        self.files.iter().sum()
+impl DiskConfig {
+    // Accepts "--capacity <n>", "--required <n>" and "--threshold <n>"
+    fn from_args(args: impl IntoIterator<Item = String>) -> Result<Self> {
+        let mut config = DiskConfig::default();
+        let mut args = args.into_iter();
+        while let Some(flag) = args.next() {
+            let value: usize = args
+                .next()
+                .ok_or(anyhow!("Missing value after {}", flag))?
+                .parse()
+                .with_context(|| format!("Invalid value for {}", flag))?;
+            match flag.as_str() {
+                "--capacity" => config.capacity = value,
+                "--required" => config.required = value,
+                "--threshold" => config.threshold = value,
+                x => return Err(anyhow!("Unknown flag: {}", x)),
+            }
+        }
+        Ok(config)
     }
+}
 
-    fn size(&self, directory_paths: &HashMap<String, Directory>) -> usize {
-        let files = self.files_size();
-        let sub_dirs: usize = self
-            .sub_directory_paths()
-            .iter()
-            .map(|dir| directory_paths[dir].size(&directory_paths))
-            .sum();
-        files + sub_dirs
-    }
+#[derive(Debug, PartialEq)]
+struct Answers {
+    small_directories: usize,
+    // Path and size of the directory to delete
+    to_delete: (String, usize),
 }
 
 fn main() -> Result<()> {
-    let result = run("input.txt")?;
-    println!("{}", result);
+    let mut args = std::env::args().skip(1).peekable();
+    if let Some(mode) = args.next_if(|arg| !arg.starts_with("--")) {
+        // These make a transcript instead of reading one
+        match mode.as_str() {
+            "transcript" => {
+                let file = args.next().ok_or(anyhow!("Missing tree file"))?;
+                let filesystem = generator::parse_tree(&read_input(&file)?)?;
+                generator::transcript(&filesystem)
+                    .iter()
+                    .for_each(|line| println!("{}", line));
+                return Ok(());
+            }
+            "generate" => {
+                let mut number = |default: usize| -> Result<usize> {
+                    Ok(args
+                        .next()
+                        .map(|n| n.parse())
+                        .transpose()?
+                        .unwrap_or(default))
+                };
+                let directories = number(1000)?;
+                let files = number(10_000)?;
+                let seed = number(0)? as u64;
+                let filesystem = generator::random_filesystem(directories, files, seed);
+                generator::transcript(&filesystem)
+                    .iter()
+                    .for_each(|line| println!("{}", line));
+                return Ok(());
+            }
+            _ => (),
+        }
+        let filesystem = build_filesystem(read_input("input.txt")?)?;
+        match mode.as_str() {
+            "explore" => {
+                let stdin = std::io::stdin();
+                Explorer::new(&filesystem).run_commands(stdin.lock(), &mut std::io::stdout())?;
+            }
+            "tree" => print!("{}", report::tree(&filesystem)),
+            "du" => print!("{}", report::du(&filesystem)),
+            "largest" => {
+                let count = match args.next() {
+                    Some(count) => count.parse()?,
+                    None => 10,
+                };
+                print!("{}", report::largest_files(&filesystem, count));
+            }
+            x => return Err(anyhow!("Unknown mode: {}", x)),
+        }
+        return Ok(());
+    }
+    let config = DiskConfig::from_args(args)?;
+    let answers = run("input.txt", &config)?;
+    println!(
+        "Part 1: Sum of directories of at most {}: {}",
+        config.threshold, answers.small_directories
+    );
+    let (path, size) = answers.to_delete;
+    println!("Part 2: Delete {} to free up {}", path, size);
     Ok(())
 }
 
-fn run(input: &str) -> Result<usize> {
+fn run(input: &str, config: &DiskConfig) -> Result<Answers> {
     // Do in a way, so it can also be tested!!
     let input = read_input(input)?;
-    let mut current_dir_path: Vec<String> = vec!["".to_owned()]; // "" is for "/"
-    let mut directories: HashMap<String, Directory> = HashMap::new();
-    let mut input = input.into_iter().skip(1);
-    while let Some(line) = input.next() {
-        match command_type(&line) {
-            Command::Cd => cd(&line, &mut current_dir_path),
-            Command::Ls => {
-                let current_path = path_to_string(&current_dir_path);
-                let output: Vec<String> = input
-                    .take_while_ref(|x| command_type(x).is_output())
-                    .collect();
-                directories.insert(current_path.clone(), ls(&output, current_path));
-            }
-            Command::Output => panic!("An 'Output' enum should never be passed here"),
-        }
+    let filesystem = build_filesystem(input)?;
+    let to_delete =
+        directory_to_delete(&filesystem, config.capacity, config.required).ok_or(anyhow!(
+            "Deleting any one directory won't free up {}",
+            config.required
+        ))?;
+    Ok(Answers {
+        small_directories: sum_at_most(&filesystem, config.threshold),
+        to_delete: (filesystem.path(to_delete), filesystem.size(to_delete)),
+    })
+}
+
+// Anything odd in the transcript is reported, but only errors stop it
+fn build_filesystem(input: Vec<String>) -> Result<FileSystem> {
+    let (filesystem, warnings) = Interpreter::new().run(&input)?;
+    for warning in warnings {
+        eprintln!("Warning: {}", warning);
     }
-    // Ok(at_most_100000(directories))
-    Ok(free_space_30_000_000(directories))
+    Ok(filesystem)
 }
 
-fn at_most_100000(directories: HashMap<String, Directory>) -> usize {
-    directories
-        .iter()
-        .map(|(_, dir)| dir.size(&directories))
-        .filter(|&x| x <= 100_000)
+fn sum_at_most(filesystem: &FileSystem, threshold: usize) -> usize {
+    filesystem
+        .directories()
+        .map(|dir| filesystem.size(dir))
+        .filter(|&x| x <= threshold)
         .sum()
 }
 
-fn free_space_30_000_000(directories: HashMap<String, Directory>) -> usize {
-    let used_space = directories["/"].size(&directories);
-    let free_space = 70_000_000 - used_space;
-    directories
-        .iter()
-        .map(|(_, dir)| dir.size(&directories))
-        .filter(|x| free_space + x >= 30_000_000)
-        .min()
-        .expect("Removing root (/) will always give enough space, so should never be empty")
-
+// The smallest directory that leaves at least `required` space free on a disk
+// of size `capacity` when deleted, or None if not even deleting "/" is enough
+fn directory_to_delete(
+    filesystem: &FileSystem,
+    capacity: usize,
+    required: usize,
+) -> Option<NodeId> {
+    let used_space = filesystem.size(filesystem.root());
+    // How much has to be deleted, which is more than `required` if the disk
+    // is overfull
+    let needed = required.saturating_add(used_space).saturating_sub(capacity);
+    filesystem
+        .directories()
+        .filter(|&dir| filesystem.size(dir) >= needed)
+        .min_by_key(|&dir| filesystem.size(dir))
 }
 
 fn read_input(input: &str) -> Result<Vec<String>> {
@@ -107,67 +180,55 @@ fn read_input(input: &str) -> Result<Vec<String>> {
     buf.lines().map(|l| Ok(l?)).collect()
 }
 
-fn path_to_string(dir_path: &Vec<String>) -> String {
-    if dir_path.len() == 1 {
-        return String::from("/");
-    }
-    format!("/{}/", dir_path[1..].join("/"))
-    // dir_path.join("")
-}
-
-fn command_type(line: &str) -> Command {
-    let mut line = line.split_whitespace();
-    match line.next() {
-        Some(x) if x == "$" => match line.next() {
-            Some(x) if x == "cd" => Command::Cd,
-            Some(x) if x == "ls" => Command::Ls,
-            _ => panic!("Not a valid command"),
-        },
-        Some(_) => Command::Output,
-        _ => panic!("Not a valid input"),
-    }
-}
-
-fn cd(line: &str, current_dir_path: &mut Vec<String>) {
-    let mut line = line.split_whitespace();
-    match line.nth(2) {
-        Some(x) if x == ".." => {
-            current_dir_path.pop();
-        }
-        Some(x) => current_dir_path.push(x.to_owned()),
-        None => panic!("Line should not be empty"),
-    }
-}
-
-fn ls(lines: &[String], path: String) -> Directory {
-    let mut files = Vec::new();
-    let mut sub_directories = Vec::new();
-
-    for line in lines.iter() {
-        let mut line = line.split_whitespace();
-        match line.next() {
-            Some(x) if x == "dir" => sub_directories.push(line.next().unwrap().to_owned()),
-            Some(x) if x.parse::<usize>().is_ok() => files.push(x.parse::<usize>().unwrap()),
-            _ => (),
-        };
-    }
-
-    Directory {
-        path,
-        files,
-        sub_directories,
-    }
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
 
     #[test]
     fn small_sample() -> Result<()> {
-        let result = run("test.txt")?;
-        // Ok(assert_eq!(result, 95437))
-        Ok(assert_eq!(result, 24933642))
+        let result = run("test.txt", &DiskConfig::default())?;
+        assert_eq!(result.small_directories, 95437);
+        assert_eq!(result.to_delete, ("/d/".to_owned(), 24933642));
+        Ok(())
+    }
+
+    #[test]
+    fn other_disks() -> Result<()> {
+        let filesystem = build_filesystem(read_input("test.txt")?)?;
+        assert_eq!(sum_at_most(&filesystem, 1000), 584);
+        // Already enough space, so the smallest directory will do
+        let dir = directory_to_delete(&filesystem, 100_000_000, 10);
+        assert_eq!(dir.map(|d| filesystem.path(d)), Some("/a/e/".to_owned()));
+        let dir = directory_to_delete(&filesystem, 50_000_000, 20_000_000);
+        assert_eq!(dir.map(|d| filesystem.path(d)), Some("/d/".to_owned()));
+        // Not even deleting everything is enough
+        assert_eq!(
+            directory_to_delete(&filesystem, 50_000_000, 60_000_000),
+            None
+        );
+        // An overfull disk needs more than `required` freed
+        let mut filesystem = FileSystem::new();
+        let root = filesystem.root();
+        let a = filesystem.add_directory(root, "a");
+        filesystem.add_file(a, "x", 10);
+        let b = filesystem.add_directory(root, "b");
+        filesystem.add_file(b, "y", 30);
+        filesystem.compute_sizes();
+        let dir = directory_to_delete(&filesystem, 30, 5);
+        assert_eq!(dir.map(|d| filesystem.path(d)), Some("/b/".to_owned()));
+        assert_eq!(directory_to_delete(&filesystem, 30, usize::MAX), None);
+        Ok(())
+    }
+
+    #[test]
+    fn config_flags() -> Result<()> {
+        let args = ["--threshold", "5", "--capacity", "10"].map(String::from);
+        let config = DiskConfig::from_args(args)?;
+        assert_eq!(config.threshold, 5);
+        assert_eq!(config.capacity, 10);
+        assert_eq!(config.required, 30_000_000);
+        assert!(DiskConfig::from_args(["--size", "5"].map(String::from)).is_err());
+        Ok(())
     }
 
     #[test]
@@ -0,0 +1,110 @@
+use crate::filesystem::{FileSystem, NodeId};
+
+// Draws the filesystem like the example in the puzzle, with directory sizes
+// added:
+//   - / (dir, size=48381165)
+//     - a (dir, size=94853)
+//       - e (dir, size=584)
+//         - i (file, size=584)
+pub fn tree(filesystem: &FileSystem) -> String {
+    let mut output = String::new();
+    // Depth-first, with the children pushed in reverse so they come out in
+    // the order they were listed
+    let mut stack = vec![(filesystem.root(), 0)];
+    while let Some((id, depth)) = stack.pop() {
+        let node = filesystem.node(id);
+        let kind = if node.is_directory() { "dir" } else { "file" };
+        output.push_str(&format!(
+            "{}- {} ({}, size={})\n",
+            "  ".repeat(depth),
+            node.name,
+            kind,
+            filesystem.size(id)
+        ));
+        stack.extend(
+            node.children()
+                .iter()
+                .rev()
+                .map(|&child| (child, depth + 1)),
+        );
+    }
+    output
+}
+
+// Every directory with its total size, largest first, like `du`
+pub fn du(filesystem: &FileSystem) -> String {
+    let mut directories: Vec<NodeId> = filesystem.directories().collect();
+    sort_by_size(filesystem, &mut directories);
+    size_table(filesystem, &directories)
+}
+
+// The `count` largest files, largest first
+pub fn largest_files(filesystem: &FileSystem, count: usize) -> String {
+    let mut files: Vec<NodeId> = filesystem.files().collect();
+    sort_by_size(filesystem, &mut files);
+    files.truncate(count);
+    size_table(filesystem, &files)
+}
+
+pub fn sort_by_size(filesystem: &FileSystem, ids: &mut [NodeId]) {
+    // Ties are broken by path, so the output is always the same
+    ids.sort_by_cached_key(|&id| (std::cmp::Reverse(filesystem.size(id)), filesystem.path(id)));
+}
+
+pub fn size_table(filesystem: &FileSystem, ids: &[NodeId]) -> String {
+    ids.iter()
+        .map(|&id| format!("{:>10}  {}\n", filesystem.size(id), filesystem.path(id)))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{build_filesystem, read_input};
+
+    #[test]
+    fn small_sample_tree() -> anyhow::Result<()> {
+        let filesystem = build_filesystem(read_input("test.txt")?)?;
+        let expected = "\
+- / (dir, size=48381165)
+  - a (dir, size=94853)
+    - e (dir, size=584)
+      - i (file, size=584)
+    - f (file, size=29116)
+    - g (file, size=2557)
+    - h.lst (file, size=62596)
+  - b.txt (file, size=14848514)
+  - c.dat (file, size=8504156)
+  - d (dir, size=24933642)
+    - j (file, size=4060174)
+    - d.log (file, size=8033020)
+    - d.ext (file, size=5626152)
+    - k (file, size=7214296)
+";
+        assert_eq!(tree(&filesystem), expected);
+        Ok(())
+    }
+
+    #[test]
+    fn small_sample_du() -> anyhow::Result<()> {
+        let filesystem = build_filesystem(read_input("test.txt")?)?;
+        let expected = "  48381165  /
+  24933642  /d/
+     94853  /a/
+       584  /a/e/
+";
+        assert_eq!(du(&filesystem), expected);
+        Ok(())
+    }
+
+    #[test]
+    fn small_sample_largest() -> anyhow::Result<()> {
+        let filesystem = build_filesystem(read_input("test.txt")?)?;
+        let expected = "  14848514  /b.txt
+   8504156  /c.dat
+";
+        assert_eq!(largest_files(&filesystem, 2), expected);
+        assert_eq!(largest_files(&filesystem, 100).lines().count(), 10);
+        Ok(())
+    }
+}
@@ -0,0 +1,336 @@
+use anyhow::{anyhow, Context, Result};
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+
+use crate::filesystem::{FileSystem, NodeId};
+
+// Something odd in the transcript that doesn't stop it from being replayed
+#[derive(Debug, PartialEq)]
+pub struct Warning {
+    pub line: usize,
+    pub message: String,
+}
+
+impl fmt::Display for Warning {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Line {}: {}", self.line, self.message)
+    }
+}
+
+// An entry in the output of `ls`
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Entry {
+    Directory(String),
+    File(String, usize),
+}
+
+impl Entry {
+    // How `ls` would show a node that is already in the filesystem
+    pub fn of(filesystem: &FileSystem, id: NodeId) -> Self {
+        let node = filesystem.node(id);
+        if node.is_directory() {
+            Entry::Directory(node.name.clone())
+        } else {
+            Entry::File(node.name.clone(), filesystem.size(id))
+        }
+    }
+
+    fn name(&self) -> &str {
+        match self {
+            Entry::Directory(name) | Entry::File(name, _) => name,
+        }
+    }
+}
+
+impl fmt::Display for Entry {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Entry::Directory(name) => write!(f, "dir {}", name),
+            Entry::File(name, size) => write!(f, "{} {}", size, name),
+        }
+    }
+}
+
+impl TryFrom<&str> for Entry {
+    type Error = anyhow::Error;
+
+    // Either "dir <name>" or "<size> <name>"
+    fn try_from(value: &str) -> Result<Self> {
+        let (first, name) = value
+            .split_once(' ')
+            .ok_or(anyhow!("Not a directory or file: {:?}", value))?;
+        if first == "dir" {
+            return Ok(Entry::Directory(name.to_owned()));
+        }
+        let size = first
+            .parse()
+            .with_context(|| format!("Not a directory or file: {:?}", value))?;
+        Ok(Entry::File(name.to_owned(), size))
+    }
+}
+
+// Replays a terminal session and builds up the filesystem it shows
+#[derive(Debug)]
+pub struct Interpreter {
+    filesystem: FileSystem,
+    current: NodeId,
+    // What `ls` showed the first time each directory was listed
+    listings: HashMap<NodeId, Vec<Entry>>,
+    warnings: Vec<Warning>,
+}
+
+impl Default for Interpreter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Interpreter {
+    pub fn new() -> Self {
+        let filesystem = FileSystem::new();
+        Interpreter {
+            current: filesystem.root(),
+            filesystem,
+            listings: HashMap::new(),
+            warnings: Vec::new(),
+        }
+    }
+
+    // Runs the whole transcript, then works out the directory sizes
+    pub fn run(mut self, lines: &[String]) -> Result<(FileSystem, Vec<Warning>)> {
+        let mut lines = lines.iter().zip(1..).peekable();
+        while let Some((line, line_nr)) = lines.next() {
+            let mut words = line.split_whitespace();
+            if words.next() != Some("$") {
+                self.warn(line_nr, format!("Output without a command: {:?}", line));
+                continue;
+            }
+            // Everything up to the next command is output
+            let mut output = Vec::new();
+            while let Some((next, next_nr)) = lines.next_if(|(l, _)| !l.starts_with('$')) {
+                output.push((next.as_str(), next_nr));
+            }
+            let result = match (words.next(), words.next(), words.next()) {
+                (Some("cd"), Some(target), None) => {
+                    if !output.is_empty() {
+                        self.warn(line_nr, format!("Unexpected output after {:?}", line));
+                    }
+                    self.cd(target, line_nr)
+                }
+                (Some("ls"), None, None) => self.ls(line_nr, &output),
+                _ => {
+                    self.warn(line_nr, format!("Unknown command: {:?}", line));
+                    continue;
+                }
+            };
+            result.with_context(|| format!("Line {}: {:?}", line_nr, line))?;
+        }
+        self.filesystem.compute_sizes();
+        Ok((self.filesystem, self.warnings))
+    }
+
+    fn warn(&mut self, line: usize, message: String) {
+        self.warnings.push(Warning { line, message });
+    }
+
+    // "/" goes to the root from anywhere, and ".." to the parent
+    pub fn cd(&mut self, target: &str, line_nr: usize) -> Result<()> {
+        self.current = match target {
+            "/" => self.filesystem.root(),
+            ".." => self
+                .filesystem
+                .node(self.current)
+                .parent
+                .ok_or(anyhow!("Can't go above /"))?,
+            name => match self.filesystem.child(self.current, name) {
+                Some(id) if self.filesystem.node(id).is_directory() => id,
+                Some(_) => return Err(anyhow!("{} is a file", name)),
+                None => {
+                    if self.listings.contains_key(&self.current) {
+                        self.warn(
+                            line_nr,
+                            format!("{} wasn't in the listing of its parent", name),
+                        );
+                    } else {
+                        self.warn(line_nr, format!("cd into {} before listing it", name));
+                    }
+                    self.filesystem.add_directory(self.current, name)
+                }
+            },
+        };
+        Ok(())
+    }
+
+    fn ls(&mut self, line_nr: usize, output: &[(&str, usize)]) -> Result<()> {
+        let mut entries = Vec::new();
+        for &(line, nr) in output {
+            entries.push(Entry::try_from(line).with_context(|| format!("Line {}", nr))?);
+        }
+        let path = self.filesystem.path(self.current);
+        if let Some(previous) = self.listings.get(&self.current) {
+            // Listing a directory again adds nothing new, but it should show
+            // the same things as the first time
+            if !same_entries(previous, &entries) {
+                self.warn(line_nr, format!("{} listed differently than before", path));
+            }
+            return Ok(());
+        }
+        // Directories that were already gone into should be listed too
+        let missing: Vec<String> = (self.filesystem.node(self.current).children().iter())
+            .map(|&child| self.filesystem.node(child).name.clone())
+            .filter(|name| !entries.iter().any(|e| e.name() == name))
+            .collect();
+        for name in missing {
+            self.warn(
+                line_nr,
+                format!("{} is missing from the listing of {}", name, path),
+            );
+        }
+        let mut listed = HashSet::new();
+        for entry in &entries {
+            let existing = self.filesystem.child(self.current, entry.name());
+            let clash = match (entry, existing) {
+                (Entry::Directory(_), Some(id)) => !self.filesystem.node(id).is_directory(),
+                (Entry::File(..), Some(id)) => self.filesystem.node(id).is_directory(),
+                (_, None) => false,
+            };
+            if clash {
+                let message = format!("{} listed as both a file and a directory", entry.name());
+                self.warn(line_nr, message);
+                continue;
+            }
+            if !listed.insert(entry.name()) {
+                let message = format!("{} listed more than once in {}", entry.name(), path);
+                self.warn(line_nr, message);
+                continue;
+            }
+            match entry {
+                Entry::Directory(name) => self.filesystem.add_directory(self.current, name),
+                Entry::File(name, size) => self.filesystem.add_file(self.current, name, *size),
+            };
+        }
+        self.listings.insert(self.current, entries);
+        Ok(())
+    }
+}
+
+// The same entries in any order, each as many times
+fn same_entries(a: &[Entry], b: &[Entry]) -> bool {
+    let (mut a, mut b) = (a.to_vec(), b.to_vec());
+    a.sort();
+    b.sort();
+    a == b
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::read_input;
+
+    fn lines(transcript: &str) -> Vec<String> {
+        transcript.lines().map(String::from).collect()
+    }
+
+    #[test]
+    fn small_sample() -> Result<()> {
+        let (filesystem, warnings) = Interpreter::new().run(&read_input("test.txt")?)?;
+        assert_eq!(warnings, Vec::new());
+        assert_eq!(filesystem.size(filesystem.root()), 48381165);
+        Ok(())
+    }
+
+    #[test]
+    fn cd_root_anywhere() -> Result<()> {
+        let transcript = "$ ls\ndir a\n$ cd a\n$ ls\ndir b\n$ cd b\n$ cd /\n$ ls\ndir a\n1 x";
+        let (filesystem, warnings) = Interpreter::new().run(&lines(transcript))?;
+        // The second listing of / has a file the first one didn't
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(warnings[0].line, 8);
+        assert_eq!(filesystem.size(filesystem.root()), 0);
+        Ok(())
+    }
+
+    #[test]
+    fn repeated_listing() -> Result<()> {
+        let transcript = "$ cd /\n$ ls\n10 x\ndir a\n$ ls\ndir a\n10 x";
+        let (filesystem, warnings) = Interpreter::new().run(&lines(transcript))?;
+        assert_eq!(warnings, Vec::new());
+        assert_eq!(filesystem.size(filesystem.root()), 10);
+        assert_eq!(filesystem.node(filesystem.root()).children().len(), 2);
+        Ok(())
+    }
+
+    #[test]
+    fn inconsistent_listing() -> Result<()> {
+        let transcript = "$ ls\n10 x\n$ ls\n20 x";
+        let (filesystem, warnings) = Interpreter::new().run(&lines(transcript))?;
+        assert_eq!(
+            warnings[0].to_string(),
+            "Line 3: / listed differently than before"
+        );
+        assert_eq!(filesystem.size(filesystem.root()), 10);
+
+        let transcript = "$ ls\n10 x\ndir x";
+        let (_, warnings) = Interpreter::new().run(&lines(transcript))?;
+        assert_eq!(
+            warnings[0].to_string(),
+            "Line 1: x listed as both a file and a directory"
+        );
+
+        // Only the first x is kept, and the second listing has another y
+        let transcript = "$ ls\n10 x\n20 x\n5 y\n$ ls\n10 x\n5 y\n5 y";
+        let (filesystem, warnings) = Interpreter::new().run(&lines(transcript))?;
+        let warnings: Vec<String> = warnings.iter().map(|w| w.to_string()).collect();
+        assert_eq!(
+            warnings,
+            vec![
+                "Line 1: x listed more than once in /",
+                "Line 5: / listed differently than before",
+            ]
+        );
+        assert_eq!(filesystem.size(filesystem.root()), 15);
+        Ok(())
+    }
+
+    #[test]
+    fn unlisted_directories() -> Result<()> {
+        let transcript = "$ cd a\n$ ls\n5 y\n$ cd ..\n$ ls\ndir b\n$ cd c";
+        let (filesystem, warnings) = Interpreter::new().run(&lines(transcript))?;
+        let warnings: Vec<String> = warnings.iter().map(|w| w.to_string()).collect();
+        assert_eq!(
+            warnings,
+            vec![
+                "Line 1: cd into a before listing it",
+                "Line 5: a is missing from the listing of /",
+                "Line 7: c wasn't in the listing of its parent",
+            ]
+        );
+        assert_eq!(filesystem.size(filesystem.root()), 5);
+        Ok(())
+    }
+
+    #[test]
+    fn unknown_commands() -> Result<()> {
+        // The output of an unknown command is skipped along with it
+        let transcript = "$ pwd\n/\n$ ls\n3 z\n$ cd /\nstray";
+        let (filesystem, warnings) = Interpreter::new().run(&lines(transcript))?;
+        let warnings: Vec<String> = warnings.iter().map(|w| w.to_string()).collect();
+        assert_eq!(
+            warnings,
+            vec![
+                "Line 1: Unknown command: \"$ pwd\"",
+                "Line 5: Unexpected output after \"$ cd /\"",
+            ]
+        );
+        assert_eq!(filesystem.size(filesystem.root()), 3);
+        Ok(())
+    }
+
+    #[test]
+    fn errors() {
+        let run = |transcript: &str| Interpreter::new().run(&lines(transcript));
+        assert!(run("$ cd ..").is_err());
+        assert!(run("$ ls\n5 x\n$ cd x").is_err());
+        assert!(run("$ ls\nfive x").is_err());
+    }
+}
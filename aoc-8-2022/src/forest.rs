@@ -0,0 +1,252 @@
+use anyhow::{anyhow, Result};
+
+// The heights of the trees, row by row. Positions are (row, column), with
+// (0, 0) in the top left corner.
+#[derive(Debug, PartialEq)]
+pub struct Grid {
+    heights: Vec<u32>,
+    width: usize,
+    height: usize,
+}
+
+impl TryFrom<&[String]> for Grid {
+    type Error = anyhow::Error;
+
+    // Every line is a row of digits, and all rows are as long
+    fn try_from(lines: &[String]) -> Result<Self> {
+        let width = lines.first().map_or(0, |line| line.len());
+        let mut heights = Vec::with_capacity(width * lines.len());
+        for (line, line_nr) in lines.iter().zip(1..) {
+            if line.len() != width {
+                return Err(anyhow!(
+                    "Line {}: Expected {} trees, found {}",
+                    line_nr,
+                    width,
+                    line.len()
+                ));
+            }
+            for c in line.chars() {
+                heights.push(c.to_digit(10).ok_or(anyhow!(
+                    "Line {}: Not a height: {:?}",
+                    line_nr,
+                    c
+                ))?);
+            }
+        }
+        Ok(Grid {
+            heights,
+            width,
+            height: lines.len(),
+        })
+    }
+}
+
+impl Grid {
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    // None outside of the grid
+    pub fn get(&self, index: (isize, isize)) -> Option<u32> {
+        self.offset(index).map(|offset| self.heights[offset])
+    }
+
+    // Where in `heights` a tree is
+    fn offset(&self, (row, column): (isize, isize)) -> Option<usize> {
+        let row = usize::try_from(row).ok().filter(|&r| r < self.height)?;
+        let column = usize::try_from(column).ok().filter(|&c| c < self.width)?;
+        Some(row * self.width + column)
+    }
+
+    pub fn positions(&self) -> impl Iterator<Item = (isize, isize)> + '_ {
+        (0..self.height as isize)
+            .flat_map(move |row| (0..self.width as isize).map(move |column| (row, column)))
+    }
+}
+
+// Which way the view from a tree goes, as an index into its viewing distances
+const UP: usize = 0;
+const LEFT: usize = 1;
+const DOWN: usize = 2;
+const RIGHT: usize = 3;
+
+// Whether each tree can be seen from outside the grid, and how far it can see
+// in each direction.
+// Every row and column is swept once in each direction with a stack of the
+// trees that could still block the view of the trees after them, which are
+// kept from tallest to shortest. A tree pops every shorter tree off the stack:
+// what is left on top is the closest tree that is at least as tall, and it is
+// only visible from the edge if nothing is left.
+#[derive(Debug)]
+pub struct Survey {
+    visible: Vec<bool>,
+    // [up, left, down, right] for every tree
+    distances: Vec<[usize; 4]>,
+}
+
+impl Survey {
+    pub fn new(trees: &Grid) -> Self {
+        let (width, height) = (trees.width, trees.height);
+        let mut survey = Survey {
+            visible: vec![false; trees.heights.len()],
+            distances: vec![[0; 4]; trees.heights.len()],
+        };
+        // Sweeping a row to the right finds how far each tree sees to the left
+        for row in 0..height {
+            let line = row * width..(row + 1) * width;
+            survey.sweep(&trees.heights, line.clone(), LEFT);
+            survey.sweep(&trees.heights, line.rev(), RIGHT);
+        }
+        for column in 0..width {
+            let line = (column..width * height).step_by(width);
+            survey.sweep(&trees.heights, line.clone(), UP);
+            survey.sweep(&trees.heights, line.rev(), DOWN);
+        }
+        survey
+    }
+
+    // `line` is the offsets of the trees in the order they are looked at, and
+    // `looking` the way back along it
+    fn sweep(&mut self, heights: &[u32], line: impl Iterator<Item = usize>, looking: usize) {
+        // (How far along the line, offset) of the trees that can block the view
+        let mut blocking: Vec<(usize, usize)> = Vec::new();
+        for (distance, offset) in line.enumerate() {
+            let height = heights[offset];
+            while blocking
+                .last()
+                .is_some_and(|&(_, blocker)| heights[blocker] < height)
+            {
+                blocking.pop();
+            }
+            match blocking.last() {
+                Some(&(blocker, _)) => self.distances[offset][looking] = distance - blocker,
+                None => {
+                    // Nothing as tall in the way, so the view goes to the edge
+                    self.distances[offset][looking] = distance;
+                    self.visible[offset] = true;
+                }
+            }
+            blocking.push((distance, offset));
+        }
+    }
+
+    pub fn is_visible(&self, trees: &Grid, index: (isize, isize)) -> bool {
+        self.visible[trees.offset(index).expect("Tree is in the grid")]
+    }
+
+    pub fn scenic_score(&self, trees: &Grid, index: (isize, isize)) -> usize {
+        self.distances[trees.offset(index).expect("Tree is in the grid")]
+            .iter()
+            .product()
+    }
+
+    // The first tree with the highest scenic score, None for an empty grid
+    pub fn best_tree(&self, trees: &Grid) -> Option<(isize, isize)> {
+        let best = self.max_scenic_score()?;
+        trees
+            .positions()
+            .find(|&index| self.scenic_score(trees, index) == best)
+    }
+
+    pub fn visible_count(&self) -> usize {
+        self.visible.iter().filter(|&&v| v).count()
+    }
+
+    // None for an empty grid
+    pub fn max_scenic_score(&self) -> Option<usize> {
+        self.distances
+            .iter()
+            .map(|distances| distances.iter().product())
+            .max()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::grid;
+    use crate::{parse_input, read_input, walker};
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    #[test]
+    fn parsing() -> Result<()> {
+        let trees = grid("123\n456")?;
+        assert_eq!((trees.width(), trees.height()), (3, 2));
+        assert_eq!(trees.get((1, 0)), Some(4));
+        assert_eq!(trees.get((0, 2)), Some(3));
+        assert_eq!(trees.get((2, 0)), None);
+        assert_eq!(trees.get((0, -1)), None);
+        assert_eq!(trees.positions().count(), 6);
+        assert!(grid("123\n45").is_err());
+        assert!(grid("1a3").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn sample_survey() -> Result<()> {
+        let trees = parse_input(&read_input("test.txt")?)?;
+        let survey = Survey::new(&trees);
+        let offset = |index| trees.offset(index).unwrap();
+        assert!(survey.visible[offset((1, 1))]);
+        assert!(!survey.visible[offset((1, 3))]);
+        assert!(!survey.visible[offset((2, 2))]);
+        assert_eq!(survey.distances[offset((1, 2))], [1, 1, 2, 2]);
+        assert_eq!(survey.distances[offset((3, 2))], [2, 2, 1, 2]);
+        assert_eq!(survey.distances[offset((0, 0))], [0, 0, 2, 2]);
+        assert_eq!(survey.scenic_score(&trees, (3, 2)), 8);
+        assert_eq!(survey.visible_count(), 21);
+        assert_eq!(survey.max_scenic_score(), Some(8));
+        assert_eq!(survey.best_tree(&trees), Some((3, 2)));
+        Ok(())
+    }
+
+    // The sweep has to agree with walking out from every tree
+    #[test]
+    fn random_grids() {
+        let mut rng = StdRng::seed_from_u64(8);
+        for _ in 0..200 {
+            let (width, height) = (rng.gen_range(1..12), rng.gen_range(1..12));
+            let highest = rng.gen_range(0..10);
+            let lines: Vec<String> = (0..height)
+                .map(|_| {
+                    (0..width)
+                        .map(|_| char::from_digit(rng.gen_range(0..=highest), 10).unwrap())
+                        .collect()
+                })
+                .collect();
+            let trees = Grid::try_from(lines.as_slice()).unwrap();
+            let survey = Survey::new(&trees);
+            for index in trees.positions() {
+                let offset = trees.offset(index).unwrap();
+                assert_eq!(
+                    survey.visible[offset],
+                    walker::is_visible(index, &trees),
+                    "{:?} in {:?}",
+                    index,
+                    lines
+                );
+                assert_eq!(
+                    survey.distances[offset],
+                    walker::viewing_distances(index, &trees),
+                    "{:?} in {:?}",
+                    index,
+                    lines
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn empty_grid() -> Result<()> {
+        let survey = Survey::new(&grid("")?);
+        assert_eq!(survey.visible_count(), 0);
+        assert_eq!(survey.max_scenic_score(), None);
+        assert_eq!(survey.best_tree(&grid("")?), None);
+        Ok(())
+    }
+}
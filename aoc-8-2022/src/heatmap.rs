@@ -0,0 +1,174 @@
+use anyhow::{anyhow, Result};
+
+use crate::forest::{Grid, Survey};
+
+type Color = (u8, u8, u8);
+
+// The tree with the highest scenic score stands out in every layer
+const BEST: Color = (40, 120, 255);
+const VISIBLE: Color = (90, 220, 90);
+const HIDDEN: Color = (45, 45, 45);
+// Low values are dark green, high values light yellow
+const LOW: Color = (0, 50, 0);
+const HIGH: Color = (255, 255, 120);
+
+// What the color of each tree shows
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Layer {
+    Heights,
+    Visibility,
+    Scenic,
+}
+
+impl TryFrom<&str> for Layer {
+    type Error = anyhow::Error;
+
+    fn try_from(value: &str) -> Result<Self> {
+        match value {
+            "heights" => Ok(Layer::Heights),
+            "visibility" => Ok(Layer::Visibility),
+            "scenic" => Ok(Layer::Scenic),
+            x => Err(anyhow!(
+                "Unknown layer: {} (expected heights, visibility or scenic)",
+                x
+            )),
+        }
+    }
+}
+
+// Somewhere between LOW and HIGH, for `t` from 0 to 1
+fn gradient(t: f64) -> Color {
+    let mix = |low: u8, high: u8| (low as f64 + (high as f64 - low as f64) * t).round() as u8;
+    (mix(LOW.0, HIGH.0), mix(LOW.1, HIGH.1), mix(LOW.2, HIGH.2))
+}
+
+// The color of every tree, row by row
+fn colors(trees: &Grid, survey: &Survey, layer: Layer) -> Vec<Vec<Color>> {
+    // Scenic scores grow very fast towards the middle of the forest, so they
+    // are drawn on a log scale to keep the rest from all looking the same
+    let highest = (survey.max_scenic_score().unwrap_or(0) as f64)
+        .ln_1p()
+        .max(1.0);
+    let best = survey.best_tree(trees);
+    (0..trees.height() as isize)
+        .map(|row| {
+            (0..trees.width() as isize)
+                .map(|column| {
+                    let index = (row, column);
+                    if Some(index) == best {
+                        return BEST;
+                    }
+                    match layer {
+                        Layer::Heights => {
+                            gradient(trees.get(index).expect("Tree is in the grid") as f64 / 9.0)
+                        }
+                        Layer::Visibility if survey.is_visible(trees, index) => VISIBLE,
+                        Layer::Visibility => HIDDEN,
+                        Layer::Scenic => {
+                            gradient((survey.scenic_score(trees, index) as f64).ln_1p() / highest)
+                        }
+                    }
+                })
+                .collect()
+        })
+        .collect()
+}
+
+// The heights on colored backgrounds, for a terminal with 24-bit color
+pub fn terminal(trees: &Grid, survey: &Survey, layer: Layer) -> String {
+    let mut output = String::new();
+    for (row, colors) in colors(trees, survey, layer).iter().enumerate() {
+        for (column, &(r, g, b)) in colors.iter().enumerate() {
+            let height = trees
+                .get((row as isize, column as isize))
+                .expect("Tree is in the grid");
+            // Black text on light colors, white on dark ones
+            let text = if r as u32 + g as u32 + b as u32 > 384 {
+                30
+            } else {
+                97
+            };
+            output.push_str(&format!("\x1b[{};48;2;{};{};{}m{}", text, r, g, b, height));
+        }
+        output.push_str("\x1b[0m\n");
+    }
+    output
+}
+
+// A binary PPM image with a square of `scale` by `scale` pixels per tree
+pub fn ppm(trees: &Grid, survey: &Survey, layer: Layer, scale: usize) -> Vec<u8> {
+    let mut image = format!(
+        "P6\n{} {}\n255\n",
+        trees.width() * scale,
+        trees.height() * scale
+    )
+    .into_bytes();
+    for colors in colors(trees, survey, layer) {
+        for _ in 0..scale {
+            for &(r, g, b) in &colors {
+                for _ in 0..scale {
+                    image.extend([r, g, b]);
+                }
+            }
+        }
+    }
+    image
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{parse_input, read_input};
+
+    fn sample() -> Result<Grid> {
+        parse_input(&read_input("test.txt")?)
+    }
+
+    #[test]
+    fn layer_colors() -> Result<()> {
+        let trees = sample()?;
+        let survey = Survey::new(&trees);
+        let heights = colors(&trees, &survey, Layer::Heights);
+        assert_eq!(heights[0][1], LOW);
+        assert_eq!(heights[3][4], HIGH);
+        assert_eq!(heights[3][2], BEST);
+        let visibility = colors(&trees, &survey, Layer::Visibility);
+        assert_eq!(visibility[1][1], VISIBLE);
+        assert_eq!(visibility[2][2], HIDDEN);
+        let scenic = colors(&trees, &survey, Layer::Scenic);
+        assert_eq!(scenic[0][0], LOW);
+        assert_eq!(scenic[3][2], BEST);
+        Ok(())
+    }
+
+    #[test]
+    fn terminal_heatmap() -> Result<()> {
+        let trees = sample()?;
+        let heatmap = terminal(&trees, &Survey::new(&trees), Layer::Visibility);
+        let lines: Vec<&str> = heatmap.lines().collect();
+        assert_eq!(lines.len(), 5);
+        assert!(lines[0].starts_with("\x1b[30;48;2;90;220;90m3"));
+        assert!(lines[3].contains("\x1b[30;48;2;40;120;255m5"));
+        assert!(lines[4].ends_with("0\x1b[0m"));
+        Ok(())
+    }
+
+    #[test]
+    fn ppm_image() -> Result<()> {
+        let trees = sample()?;
+        let image = ppm(&trees, &Survey::new(&trees), Layer::Scenic, 2);
+        let header = b"P6\n10 10\n255\n";
+        assert!(image.starts_with(header));
+        assert_eq!(image.len(), header.len() + 10 * 10 * 3);
+        // The best tree is at row 3, column 2, so pixel (7, 5) is part of it
+        let pixel = header.len() + (7 * 10 + 5) * 3;
+        assert_eq!(image[pixel..pixel + 3], [BEST.0, BEST.1, BEST.2]);
+        Ok(())
+    }
+
+    #[test]
+    fn layer_names() {
+        assert_eq!(Layer::try_from("scenic").unwrap(), Layer::Scenic);
+        assert!(Layer::try_from("colors").is_err());
+    }
+}
@@ -1,28 +1,69 @@
 use std::{
-    collections::HashMap,
     fs::File,
     io::{BufRead, BufReader},
 };
 
-use anyhow::Result;
+use anyhow::{anyhow, Result};
 
-enum Direction {
-    Right,
-    Left,
-    Up,
-    Down,
+mod forest;
+use forest::{Grid, Survey};
+
+mod heatmap;
+use heatmap::Layer;
+
+mod walker;
+
+// Pixels per tree, in both directions, in a heatmap image
+const PPM_SCALE: usize = 4;
+
+#[derive(Debug, PartialEq)]
+struct Answers {
+    visible: usize,
+    scenic_score: usize,
 }
 
 fn main() -> Result<()> {
-    let result = run("input.txt")?;
-    println!("{}", result);
+    let mut args = std::env::args().skip(1);
+    let brute_force = match args.next().as_deref() {
+        None => false,
+        // Walks out from every tree instead, to check the answers
+        Some("brute-force") => true,
+        Some("heatmap") => {
+            let layer = Layer::try_from(args.next().as_deref().unwrap_or("scenic"))?;
+            let trees = parse_input(&read_input("input.txt")?)?;
+            let survey = Survey::new(&trees);
+            match args.next() {
+                Some(file) => {
+                    std::fs::write(file, heatmap::ppm(&trees, &survey, layer, PPM_SCALE))?
+                }
+                None => print!("{}", heatmap::terminal(&trees, &survey, layer)),
+            }
+            return Ok(());
+        }
+        Some(x) => return Err(anyhow!("Unknown mode: {}", x)),
+    };
+    let answers = run("input.txt", brute_force)?;
+    println!("Part 1: Trees visible from outside: {}", answers.visible);
+    println!("Part 2: Highest scenic score: {}", answers.scenic_score);
     Ok(())
 }
 
-fn run(input: &str) -> Result<usize> {
+fn run(input: &str, brute_force: bool) -> Result<Answers> {
     let input = read_input(input)?;
-    let input = parse_input(input);
-    Ok(max_scenic_score(input))
+    let trees = parse_input(&input)?;
+    let (visible, scenic_score) = if brute_force {
+        (
+            walker::visible_count(&trees),
+            walker::max_scenic_score(&trees),
+        )
+    } else {
+        let survey = Survey::new(&trees);
+        (survey.visible_count(), survey.max_scenic_score())
+    };
+    Ok(Answers {
+        visible,
+        scenic_score: scenic_score.ok_or(anyhow!("There are no trees"))?,
+    })
 }
 
 fn read_input(input: &str) -> Result<Vec<String>> {
@@ -31,94 +72,42 @@ fn read_input(input: &str) -> Result<Vec<String>> {
     buf.lines().map(|x| Ok(x?)).collect()
 }
 
-fn parse_input(input: Vec<String>) -> HashMap<(isize, isize), u32> {
-    (0..).zip(input
-        .into_iter())
-        .map(|(row, line)| (parse_tree_line(row, line).into_iter()))
-        .flatten()
-        .collect()
-}
-
-fn parse_tree_line(row: isize, line: String) -> Vec<((isize, isize), u32)> {
-    (0..).zip(line.chars())
-        .map(move |(i, c)| ((row, i), c.to_digit(10).unwrap()))
-        .collect::<Vec<_>>()
-}
-
-fn visible_trees(
-    direction: Direction,
-    mut index: (isize, isize),
-    trees: &HashMap<(isize, isize), u32>,
-) -> usize {
-    let mut count = 0;
-    let tree_height = trees[&index];
-    loop {
-        match direction {
-            Direction::Right => index.0 += 1,
-            Direction::Up => index.1 += 1,
-            // Way to do this, if using (usize, usize) as index, rather than (isize, isize).
-            Direction::Left => match index.0.checked_sub(1) {
-                Some(x) => index.0 -= x,
-                None => break,
-            }
-            // Way that only works when using (isize, isize)
-            Direction::Down => index.1 -= 1,
-        }
-        match trees.get(&index) {
-            Some(x) if *x < tree_height => {
-                count += 1;
-                continue;
-            }
-            Some(_) => {
-                count += 1;
-                break;
-            }
-            None => break,
-        }
-    }
-    count
-}
-
-fn max_scenic_score(trees: HashMap<(isize, isize), u32>) -> usize {
-    trees
-        .keys()
-        .map(|&i| scenic_score(i, &trees))
-        .max()
-        .expect("Should not be empty")
-}
-
-fn scenic_score(index: (isize, isize), trees: &HashMap<(isize, isize), u32>) -> usize {
-    let right = visible_trees(Direction::Right, index, trees);
-    let left = visible_trees(Direction::Left, index, trees);
-    let over = visible_trees(Direction::Up, index, trees);
-    let under = visible_trees(Direction::Down, index, trees);
-
-    right * left * over * under
+fn parse_input(input: &[String]) -> Result<Grid> {
+    Grid::try_from(input)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    // For small grids written out in the tests, here and in the other modules
+    pub(crate) fn grid(text: &str) -> Result<Grid> {
+        let lines: Vec<String> = text.lines().map(String::from).collect();
+        parse_input(&lines)
+    }
+
     #[test]
     fn small_sample() -> Result<()> {
-        let result = run("test.txt")?;
-        assert_eq!(8, result);
+        for brute_force in [false, true] {
+            let result = run("test.txt", brute_force)?;
+            assert_eq!(21, result.visible);
+            assert_eq!(8, result.scenic_score);
+        }
         Ok(())
     }
 
     #[test]
     fn can_read_input() -> Result<()> {
         let input = read_input("input.txt")?;
-        dbg!(input);
-        panic!()
+        assert_eq!(input.len(), 99);
+        Ok(())
     }
 
     #[test]
     fn can_parse_input() -> Result<()> {
         let input = read_input("test.txt")?;
-        let input = parse_input(input);
-        dbg!(input);
-        panic!()
+        let input = parse_input(&input)?;
+        assert_eq!((input.width(), input.height()), (5, 5));
+        Ok(())
     }
 }
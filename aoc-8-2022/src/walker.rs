@@ -0,0 +1,136 @@
+use crate::forest::Grid;
+
+// The straightforward way of finding both answers: walking out from every
+// tree in all four directions. Much slower than `forest::Survey`, but easy to
+// check by hand, so the survey is tested against it.
+
+#[derive(Debug, Clone, Copy)]
+enum Direction {
+    Right,
+    Left,
+    Up,
+    Down,
+}
+
+impl Direction {
+    // How (row, column) changes with each step. Rows go down the grid, and
+    // columns to the right.
+    fn step(self) -> (isize, isize) {
+        match self {
+            Direction::Right => (0, 1),
+            Direction::Left => (0, -1),
+            Direction::Up => (-1, 0),
+            Direction::Down => (1, 0),
+        }
+    }
+}
+
+// Trees on the edge are always visible
+pub fn is_visible(index: (isize, isize), trees: &Grid) -> bool {
+    let tree_height = trees.get(index).expect("Tree is in the grid");
+    let (row, column) = index;
+    let lower = |position| trees.get(position).expect("Tree is in the grid") < tree_height;
+    let right = (column + 1..trees.width() as isize).all(|c| lower((row, c)));
+    let left = (0..column).all(|c| lower((row, c)));
+    let over = (0..row).all(|r| lower((r, column)));
+    let under = (row + 1..trees.height() as isize).all(|r| lower((r, column)));
+    right || left || over || under
+}
+
+pub fn visible_count(trees: &Grid) -> usize {
+    trees.positions().filter(|&i| is_visible(i, trees)).count()
+}
+
+// How many trees can be seen from the tree at `index` looking in `direction`:
+// up to and including the first one that is at least as tall, or up to the
+// edge. Zero for a tree on that edge.
+fn visible_trees(direction: Direction, index: (isize, isize), trees: &Grid) -> usize {
+    let tree_height = trees.get(index).expect("Tree is in the grid");
+    let (row_step, column_step) = direction.step();
+    let (mut row, mut column) = index;
+    let mut count = 0;
+    loop {
+        row += row_step;
+        column += column_step;
+        match trees.get((row, column)) {
+            Some(height) => {
+                count += 1;
+                if height >= tree_height {
+                    break;
+                }
+            }
+            None => break,
+        }
+    }
+    count
+}
+
+pub fn max_scenic_score(trees: &Grid) -> Option<usize> {
+    trees.positions().map(|i| scenic_score(i, trees)).max()
+}
+
+// [up, left, down, right], in the same order as `forest::Survey` keeps them
+pub fn viewing_distances(index: (isize, isize), trees: &Grid) -> [usize; 4] {
+    [
+        Direction::Up,
+        Direction::Left,
+        Direction::Down,
+        Direction::Right,
+    ]
+    .map(|direction| visible_trees(direction, index, trees))
+}
+
+pub fn scenic_score(index: (isize, isize), trees: &Grid) -> usize {
+    viewing_distances(index, trees).iter().product()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::grid;
+    use crate::{parse_input, read_input};
+    use anyhow::Result;
+
+    #[test]
+    fn visibility() -> Result<()> {
+        let trees = parse_input(&read_input("test.txt")?)?;
+        assert!(is_visible((1, 1), &trees));
+        assert!(!is_visible((1, 3), &trees));
+        assert!(!is_visible((2, 2), &trees));
+        assert!(is_visible((0, 4), &trees));
+        assert_eq!(visible_count(&trees), 21);
+        assert_eq!(max_scenic_score(&trees), Some(8));
+        Ok(())
+    }
+
+    #[test]
+    fn sample_viewing_distances() -> Result<()> {
+        let trees = parse_input(&read_input("test.txt")?)?;
+        // The two trees worked out in the puzzle, as [up, left, down, right]
+        assert_eq!(viewing_distances((1, 2), &trees), [1, 1, 2, 2]);
+        assert_eq!(scenic_score((1, 2), &trees), 4);
+        assert_eq!(viewing_distances((3, 2), &trees), [2, 2, 1, 2]);
+        assert_eq!(scenic_score((3, 2), &trees), 8);
+        // Seeing over shorter trees all the way to the edge
+        assert_eq!(viewing_distances((2, 0), &trees), [2, 0, 2, 4]);
+        assert_eq!(viewing_distances((4, 3), &trees), [4, 3, 0, 1]);
+        Ok(())
+    }
+
+    #[test]
+    fn edges() -> Result<()> {
+        let trees = grid("123\n456\n789")?;
+        assert_eq!(viewing_distances((0, 0), &trees), [0, 0, 1, 1]);
+        assert_eq!(viewing_distances((0, 2), &trees), [0, 2, 1, 0]);
+        assert_eq!(viewing_distances((2, 0), &trees), [2, 0, 0, 1]);
+        assert_eq!(viewing_distances((2, 2), &trees), [2, 2, 0, 0]);
+        assert_eq!(viewing_distances((1, 1), &trees), [1, 1, 1, 1]);
+        // A single row or column only sees along itself
+        let trees = grid("0120")?;
+        assert_eq!(viewing_distances((0, 2), &trees), [0, 2, 0, 1]);
+        let trees = grid("5\n1\n5")?;
+        assert_eq!(viewing_distances((0, 0), &trees), [0, 0, 2, 0]);
+        assert_eq!(scenic_score((1, 0), &trees), 0);
+        Ok(())
+    }
+}
@@ -1,56 +1,113 @@
-use std::{collections::HashSet, fs::File, io::BufRead, io::BufReader};
+use std::{
+    collections::{HashMap, HashSet},
+    fmt,
+    fs::File,
+    io::BufRead,
+    io::BufReader,
+};
 
 use anyhow::{anyhow, Result};
 
+mod render;
+
+mod script;
+
+#[derive(Debug, PartialEq, Clone, Copy)]
 enum Direction {
     Up,
     Down,
     Right,
     Left,
+    UpRight,
+    UpLeft,
+    DownRight,
+    DownLeft,
+}
+
+impl Direction {
+    // How the head moves in one step, with up as positive y
+    fn delta(&self) -> (isize, isize) {
+        match self {
+            Direction::Up => (0, 1),
+            Direction::Down => (0, -1),
+            Direction::Right => (1, 0),
+            Direction::Left => (-1, 0),
+            Direction::UpRight => (1, 1),
+            Direction::UpLeft => (-1, 1),
+            Direction::DownRight => (1, -1),
+            Direction::DownLeft => (-1, -1),
+        }
+    }
+
+    // The way it is written in the input
+    fn name(&self) -> &'static str {
+        match self {
+            Direction::Up => "U",
+            Direction::Down => "D",
+            Direction::Right => "R",
+            Direction::Left => "L",
+            Direction::UpRight => "UR",
+            Direction::UpLeft => "UL",
+            Direction::DownRight => "DR",
+            Direction::DownLeft => "DL",
+        }
+    }
 }
 
+#[derive(Debug, PartialEq, Clone)]
 struct Motion {
     direction: Direction,
     steps: u32,
 }
 
+// Knots whose positions are tracked to find both answers: the one right after
+// the head, and the tail of a rope of ten knots
+const ROPE_LENGTH: usize = 10;
+const SHORT_TAIL: usize = 1;
+const LONG_TAIL: usize = 9;
+
+// Pixels per position, in both directions, in an image of visited positions
+const PPM_SCALE: usize = 2;
+
 #[derive(Debug)]
 struct Rope {
     // First is head, last is tail
-    rope_location: [(isize, isize); 10],
-    // tail_location: (isize, isize),
-    // head_visited: HashSet<(isize, isize)>,
-    tail_visited: HashSet<(isize, isize)>,
+    knots: Vec<(isize, isize)>,
+    // Every position each tracked knot has been in
+    visited: HashMap<usize, HashSet<(isize, isize)>>,
 }
 
 impl Rope {
-    fn new() -> Self {
-        Self {
-            // Could make this more generalizable, but array length must be known at compile time,
-            // and using a vector might produce some problems with multiple mut references at
-            // the same time...
-            rope_location: [(0, 0); 10],
-            tail_visited: HashSet::from([(0, 0)]),
+    // A rope of `length` knots, all at the start, which remembers where the
+    // knots in `tracked` have been. Each knot only follows the one before it,
+    // so knot 1 moves the same as the tail of a rope of two knots.
+    fn new(length: usize, tracked: &[usize]) -> Result<Self> {
+        if length == 0 {
+            return Err(anyhow!("A rope needs at least one knot"));
+        }
+        if let Some(knot) = tracked.iter().find(|&&knot| knot >= length) {
+            return Err(anyhow!("No knot {} in a rope of {} knots", knot, length));
         }
+        Ok(Self {
+            knots: vec![(0, 0); length],
+            visited: tracked
+                .iter()
+                .map(|&knot| (knot, HashSet::from([(0, 0)])))
+                .collect(),
+        })
     }
 
     fn step(&mut self, direction: &Direction) {
-        let head = self.rope_location.first_mut().expect("Should not be empty");
-        match direction {
-            Direction::Up => head.1 += 1,
-            Direction::Down => head.1 -= 1,
-            Direction::Right => head.0 += 1,
-            Direction::Left => head.0 -= 1,
-        }
-        for i in 1..10 {
+        let head = self.knots.first_mut().expect("Should not be empty");
+        let (delta_x, delta_y) = direction.delta();
+        head.0 += delta_x;
+        head.1 += delta_y;
+        for i in 1..self.knots.len() {
             self.move_knot(i);
         }
-        self.tail_visited.insert(
-            self.rope_location
-                .last()
-                .expect("Should not be empty")
-                .clone(),
-        );
+        for (&knot, visited) in self.visited.iter_mut() {
+            visited.insert(self.knots[knot]);
+        }
     }
 
     fn movement(&mut self, motion: &Motion) {
@@ -60,16 +117,22 @@ impl Rope {
         }
     }
 
-    fn tail_visited_count(&self) -> usize {
-        self.tail_visited.len()
+    fn knots(&self) -> &[(isize, isize)] {
+        &self.knots
+    }
+
+    // None if the knot isn't tracked
+    fn visited(&self, knot: usize) -> Option<&HashSet<(isize, isize)>> {
+        self.visited.get(&knot)
+    }
+
+    fn visited_count(&self, knot: usize) -> Option<usize> {
+        self.visited(knot).map(|visited| visited.len())
     }
 
     fn move_knot(&mut self, index: usize) {
-        let previous_knot = self.rope_location[index - 1];
-        let knot = self
-            .rope_location
-            .get_mut(index)
-            .expect("Should not be empty");
+        let previous_knot = self.knots[index - 1];
+        let knot = &mut self.knots[index];
         let delta_x = previous_knot.0 - knot.0;
         let delta_y = previous_knot.1 - knot.1;
         if delta_x.abs() > 1 || delta_y.abs() > 1 {
@@ -79,19 +142,10 @@ impl Rope {
     }
 }
 
-impl TryFrom<String> for Motion {
-    type Error = anyhow::Error;
-
-    fn try_from(value: String) -> Result<Self> {
-        // Needs to be of the form: "D 14", or likewise
-        let mut value = value.split_whitespace();
-        let direction = value.next().ok_or(anyhow!("Empty string"))?;
-        let direction = Direction::try_from(direction)?;
-        let steps = value
-            .next()
-            .ok_or(anyhow!("Nothing after whitespace"))?
-            .parse()?;
-        Ok(Motion { direction, steps })
+impl fmt::Display for Motion {
+    // The same way it is written in the input
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} {}", self.direction.name(), self.steps)
     }
 }
 
@@ -100,10 +154,14 @@ impl TryFrom<&str> for Direction {
 
     fn try_from(value: &str) -> Result<Self> {
         let direction = match value {
-            x if x == "U" => Direction::Up,
-            x if x == "D" => Direction::Down,
-            x if x == "R" => Direction::Right,
-            x if x == "L" => Direction::Left,
+            "U" => Direction::Up,
+            "D" => Direction::Down,
+            "R" => Direction::Right,
+            "L" => Direction::Left,
+            "UR" => Direction::UpRight,
+            "UL" => Direction::UpLeft,
+            "DR" => Direction::DownRight,
+            "DL" => Direction::DownLeft,
             x => return Err(anyhow!("Not a valid direction: {}", x)),
         };
         Ok(direction)
@@ -111,21 +169,77 @@ impl TryFrom<&str> for Direction {
 }
 
 fn main() -> Result<()> {
-    let result = run("input.txt")?;
-    println!("Result is: {}", result);
+    let mut args = std::env::args().skip(1);
+    match args.next().as_deref() {
+        None => {
+            let rope = run("input.txt", ROPE_LENGTH, &[SHORT_TAIL, LONG_TAIL])?;
+            let count = |knot| rope.visited_count(knot).expect("Knot is tracked");
+            println!(
+                "Part 1: Positions visited by knot {}: {}",
+                SHORT_TAIL,
+                count(SHORT_TAIL)
+            );
+            println!(
+                "Part 2: Positions visited by knot {}: {}",
+                LONG_TAIL,
+                count(LONG_TAIL)
+            );
+        }
+        // "track 20 1 5 19" follows knots 1, 5 and 19 of a rope of 20 knots
+        Some("track") => {
+            let length = args.next().ok_or(anyhow!("Missing rope length"))?.parse()?;
+            let tracked = args
+                .map(|knot| knot.parse())
+                .collect::<Result<Vec<usize>, _>>()?;
+            let rope = run("input.txt", length, &tracked)?;
+            for knot in tracked {
+                let count = rope.visited_count(knot).expect("Knot is tracked");
+                println!("Knot {} visited {} positions", knot, count);
+            }
+        }
+        // "replay motion test.txt 10" draws a rope of 10 knots after each
+        // motion, and "replay step" after every single step
+        Some("replay") => {
+            let every_step = match args.next().as_deref() {
+                Some("motion") => false,
+                Some("step") => true,
+                _ => return Err(anyhow!("Expected replay motion or replay step")),
+            };
+            let file = args.next().ok_or(anyhow!("Missing input file"))?;
+            let length = match args.next() {
+                Some(length) => length.parse()?,
+                None => ROPE_LENGTH,
+            };
+            let motions = parse_input(read_input(&file)?)?;
+            render::replay(&motions, length, every_step, &mut std::io::stdout())?;
+        }
+        // "visited 9" draws where knot 9 has been, and "visited 9 out.ppm"
+        // saves it as an image instead
+        Some("visited") => {
+            let knot = match args.next() {
+                Some(knot) => knot.parse()?,
+                None => LONG_TAIL,
+            };
+            let rope = run("input.txt", ROPE_LENGTH, &[knot])?;
+            let visited = rope.visited(knot).expect("Knot is tracked");
+            match args.next() {
+                Some(file) => std::fs::write(file, render::visited_ppm(visited, PPM_SCALE))?,
+                None => print!("{}", render::draw_visited(visited)),
+            }
+        }
+        Some(x) => return Err(anyhow!("Unknown mode: {}", x)),
+    }
     Ok(())
 }
 
-fn run(input: &str) -> Result<usize> {
+fn run(input: &str, length: usize, tracked: &[usize]) -> Result<Rope> {
     let input = read_input(input)?;
     let movement_commands = parse_input(input)?;
-    let mut rope = Rope::new();
+    let mut rope = Rope::new(length, tracked)?;
     for command in movement_commands {
         rope.movement(&command);
-        // dbg!(&rope);
     }
-    // println!("Answer is: {}", rope.tail_visited_count());
-    Ok(rope.tail_visited_count())
+    Ok(rope)
 }
 
 fn read_input(input: &str) -> Result<Vec<String>> {
@@ -134,42 +248,59 @@ fn read_input(input: &str) -> Result<Vec<String>> {
     buf.lines().map(|x| Ok(x?)).collect()
 }
 
+// The input is a script of motions, see `script` for everything it can do
 fn parse_input(input: Vec<String>) -> Result<Vec<Motion>> {
-    input
-        .into_iter()
-        .map(|x| Ok(Motion::try_from(x)?))
-        .collect()
+    script::parse_script(&input)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    fn visited(input: &str, knot: usize) -> Result<Option<usize>> {
+        let rope = run(input, ROPE_LENGTH, &[SHORT_TAIL, LONG_TAIL])?;
+        Ok(rope.visited_count(knot))
+    }
+
     #[test]
     fn small_sample() -> Result<()> {
-        let result = run("test.txt")?;
-        assert_eq!(result, 1);
+        assert_eq!(visited("test.txt", SHORT_TAIL)?, Some(13));
+        assert_eq!(visited("test.txt", LONG_TAIL)?, Some(1));
         Ok(())
     }
 
     #[test]
     fn small_sample_2() -> Result<()> {
-        let result = run("test2.txt")?;
-        assert_eq!(result, 36);
+        assert_eq!(visited("test2.txt", LONG_TAIL)?, Some(36));
+        assert_eq!(visited("test2.txt", 5)?, None);
         Ok(())
     }
 
     #[test]
     fn few_steps() -> Result<()> {
-        let mut rope = Rope::new();
+        let mut rope = Rope::new(ROPE_LENGTH, &[LONG_TAIL])?;
         rope.step(&Direction::Up);
-        dbg!(&rope);
+        assert_eq!(rope.knots[..2], [(0, 1), (0, 0)]);
         rope.step(&Direction::Up);
-        dbg!(&rope);
+        assert_eq!(rope.knots[..2], [(0, 2), (0, 1)]);
         rope.step(&Direction::Right);
-        dbg!(&rope);
+        assert_eq!(rope.knots[..3], [(1, 2), (0, 1), (0, 0)]);
         rope.step(&Direction::Right);
-        dbg!(&rope);
-        panic!()
+        assert_eq!(rope.knots[..4], [(2, 2), (1, 2), (1, 1), (0, 0)]);
+        assert_eq!(rope.visited_count(LONG_TAIL), Some(1));
+        Ok(())
+    }
+
+    #[test]
+    fn rope_lengths() -> Result<()> {
+        // The tail of a short rope moves like knot 1 of a long one
+        let short = run("test.txt", 2, &[1])?;
+        assert_eq!(short.visited_count(1), Some(13));
+        // A lone head is its own tail
+        let head = run("test.txt", 1, &[0])?;
+        assert_eq!(head.visited_count(0), Some(21));
+        assert!(Rope::new(0, &[]).is_err());
+        assert!(Rope::new(10, &[10]).is_err());
+        Ok(())
     }
 }
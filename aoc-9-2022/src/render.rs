@@ -0,0 +1,227 @@
+use anyhow::{anyhow, Result};
+use std::collections::HashSet;
+use std::io::Write;
+
+use crate::{Motion, Rope};
+
+type Position = (isize, isize);
+
+// The part of the plane that gets drawn. Always includes the start.
+#[derive(Debug, PartialEq, Clone, Copy)]
+struct Bounds {
+    min: Position,
+    max: Position,
+}
+
+impl Bounds {
+    fn around<'a>(positions: impl IntoIterator<Item = &'a Position>) -> Self {
+        let mut bounds = Bounds {
+            min: (0, 0),
+            max: (0, 0),
+        };
+        for &position in positions {
+            bounds.include(position);
+        }
+        bounds
+    }
+
+    fn include(&mut self, (x, y): Position) {
+        self.min = (self.min.0.min(x), self.min.1.min(y));
+        self.max = (self.max.0.max(x), self.max.1.max(y));
+    }
+
+    fn width(&self) -> usize {
+        (self.max.0 - self.min.0 + 1) as usize
+    }
+
+    fn height(&self) -> usize {
+        (self.max.1 - self.min.1 + 1) as usize
+    }
+
+    // One character per position, with up at the top like in the puzzle
+    fn draw(&self, cell: impl Fn(Position) -> char) -> String {
+        let mut output = String::new();
+        for y in (self.min.1..=self.max.1).rev() {
+            output.extend((self.min.0..=self.max.0).map(|x| cell((x, y))));
+            output.push('\n');
+        }
+        output
+    }
+}
+
+// Knots that can be told apart in a drawing: the head and 35 more
+const LABELED_KNOTS: usize = 36;
+
+// H for the head, then 1 to 9, and on to letters for longer ropes
+fn knot_label(knot: usize) -> char {
+    match knot {
+        0 => 'H',
+        knot => char::from_digit(knot as u32, 36).expect("Rope is short enough to label"),
+    }
+}
+
+// Knots closer to the head are drawn on top of the ones behind them, and all
+// of them on top of the start
+fn draw_rope(rope: &Rope, bounds: &Bounds) -> String {
+    bounds.draw(
+        |position| match rope.knots().iter().position(|&knot| knot == position) {
+            Some(knot) => knot_label(knot),
+            None if position == (0, 0) => 's',
+            None => '.',
+        },
+    )
+}
+
+// Every state of a rope of `length` knots as it makes the motions, either
+// after each whole motion or after each step, like the examples in the puzzle.
+// All frames are the same size, so they can be flipped through. Each one is
+// written to `out` as soon as it is drawn.
+pub fn replay(
+    motions: &[Motion],
+    length: usize,
+    every_step: bool,
+    out: &mut impl Write,
+) -> Result<()> {
+    if length > LABELED_KNOTS {
+        return Err(anyhow!(
+            "Ropes of more than {} knots cannot be drawn",
+            LABELED_KNOTS
+        ));
+    }
+    let mut rope = Rope::new(length, &[])?;
+    let mut bounds = Bounds::around(&[]);
+    for motion in motions {
+        for _ in 0..motion.steps {
+            rope.step(&motion.direction);
+            rope.knots().iter().for_each(|&knot| bounds.include(knot));
+        }
+    }
+
+    let mut rope = Rope::new(length, &[])?;
+    writeln!(out, "== Initial State ==\n\n{}", draw_rope(&rope, &bounds))?;
+    for motion in motions {
+        writeln!(out, "== {} ==\n", motion)?;
+        for _ in 0..motion.steps {
+            rope.step(&motion.direction);
+            if every_step {
+                writeln!(out, "{}", draw_rope(&rope, &bounds))?;
+            }
+        }
+        if !every_step {
+            writeln!(out, "{}", draw_rope(&rope, &bounds))?;
+        }
+    }
+    Ok(())
+}
+
+// Visited positions as '#', like the puzzle shows the tail's
+pub fn draw_visited(visited: &HashSet<Position>) -> String {
+    Bounds::around(visited).draw(|position| match position {
+        (0, 0) => 's',
+        position if visited.contains(&position) => '#',
+        _ => '.',
+    })
+}
+
+// A binary PPM image of the visited positions in white, with the start in
+// red, and `scale` by `scale` pixels per position
+pub fn visited_ppm(visited: &HashSet<Position>, scale: usize) -> Vec<u8> {
+    let bounds = Bounds::around(visited);
+    let mut image = format!(
+        "P6\n{} {}\n255\n",
+        bounds.width() * scale,
+        bounds.height() * scale
+    )
+    .into_bytes();
+    for line in draw_visited(visited).lines() {
+        let row: Vec<u8> = line
+            .chars()
+            .flat_map(|cell| {
+                let color = match cell {
+                    's' => [255, 0, 0],
+                    '#' => [255, 255, 255],
+                    _ => [0, 0, 0],
+                };
+                std::iter::repeat_n(color, scale).flatten()
+            })
+            .collect();
+        for _ in 0..scale {
+            image.extend(&row);
+        }
+    }
+    image
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{parse_input, read_input, run};
+
+    fn frames(motions: &[Motion], length: usize, every_step: bool) -> Result<String> {
+        let mut out = Vec::new();
+        replay(motions, length, every_step, &mut out)?;
+        Ok(String::from_utf8(out)?)
+    }
+
+    #[test]
+    fn sample_motions() -> Result<()> {
+        let motions = parse_input(read_input("test.txt")?)?;
+        let frames = frames(&motions, 10, false)?;
+        let frames: Vec<&str> = frames.split("\n\n").collect();
+        assert_eq!(frames[0], "== Initial State ==");
+        assert_eq!(frames[1], "......\n......\n......\n......\nH.....");
+        assert_eq!(frames[2], "== R 4 ==");
+        assert_eq!(frames[3], "......\n......\n......\n......\n4321H.");
+        assert_eq!(frames[4], "== U 4 ==");
+        assert_eq!(frames[5], "....H.\n....1.\n..432.\n.5....\n6.....");
+        Ok(())
+    }
+
+    #[test]
+    fn every_step() -> Result<()> {
+        let motions = parse_input(vec!["R 2".to_owned(), "U 1".to_owned()])?;
+        let frames = frames(&motions, 2, true)?;
+        assert_eq!(
+            frames,
+            "== Initial State ==\n\n...\nH..\n\n\
+             == R 2 ==\n\n...\n1H.\n\n...\ns1H\n\n\
+             == U 1 ==\n\n..H\ns1.\n\n"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn sample_visited() -> Result<()> {
+        let rope = run("test.txt", 2, &[1])?;
+        let visited = rope.visited(1).expect("Knot is tracked");
+        // Like the puzzle shows it, but only as wide as what was visited
+        assert_eq!(draw_visited(visited), "..##.\n...##\n.####\n....#\ns###.\n");
+        let image = visited_ppm(visited, 2);
+        let header = b"P6\n10 10\n255\n";
+        assert!(image.starts_with(header));
+        assert_eq!(image.len(), header.len() + 10 * 10 * 3);
+        // The start is in the bottom left corner
+        assert_eq!(image[image.len() - 10 * 3..][..3], [255, 0, 0]);
+        assert_eq!(image[header.len()..][..3], [0, 0, 0]);
+        Ok(())
+    }
+
+    #[test]
+    fn labels() {
+        assert_eq!(knot_label(0), 'H');
+        assert_eq!(knot_label(9), '9');
+        assert_eq!(knot_label(10), 'a');
+        assert_eq!(knot_label(35), 'z');
+    }
+
+    #[test]
+    fn too_long_to_draw() -> Result<()> {
+        let motions = parse_input(vec!["R 1".to_owned()])?;
+        assert!(frames(&motions, 36, false).is_ok());
+        assert_eq!(
+            frames(&motions, 37, false).unwrap_err().to_string(),
+            "Ropes of more than 36 knots cannot be drawn"
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,268 @@
+use anyhow::{anyhow, Context, Result};
+use std::fmt;
+
+use crate::{Direction, Motion};
+
+// Motions can be written a few more ways than in the puzzle input, to make it
+// easier to script scenarios. Each line follows this grammar:
+//
+//   line    = [ items ] [ "#" comment ]
+//   items   = item { "," item }
+//   item    = repeat | motion
+//   repeat  = count "x" "(" items ")"
+//   motion  = direction steps
+//
+// where a direction is one of U, D, L, R, UR, UL, DR or DL. So
+// "3x(R 2, U 1) # stairs" is R 2, U 1, R 2, U 1, R 2, U 1. Repeats can be
+// nested, but have to end on the line they start on.
+pub fn parse_script(lines: &[String]) -> Result<Vec<Motion>> {
+    let mut motions = Vec::new();
+    let mut steps: u64 = 0;
+    for (line, line_nr) in lines.iter().zip(1..) {
+        let line_motions =
+            parse_line(line).with_context(|| format!("Line {}: {:?}", line_nr, line))?;
+        steps += line_motions.iter().map(|m| m.steps as u64).sum::<u64>();
+        motions.extend(line_motions);
+        check_length(motions.len())?;
+        if steps > MOST_STEPS {
+            return Err(anyhow!("More than {} steps", MOST_STEPS));
+        }
+    }
+    Ok(motions)
+}
+
+// Repeats are expanded as they are parsed, so a few nested ones could
+// otherwise ask for more motions than fit in memory
+const MOST_MOTIONS: usize = 1_000_000;
+// Every step is simulated, so a few long motions could take just as long
+const MOST_STEPS: u64 = 10_000_000;
+
+fn check_length(motions: usize) -> Result<()> {
+    if motions > MOST_MOTIONS {
+        return Err(anyhow!("More than {} motions", MOST_MOTIONS));
+    }
+    Ok(())
+}
+
+#[derive(Debug, PartialEq)]
+enum Token {
+    Word(String),
+    Number(u32),
+    Comma,
+    Open,
+    Close,
+}
+
+impl fmt::Display for Token {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Token::Word(word) => write!(f, "{:?}", word),
+            Token::Number(number) => write!(f, "{}", number),
+            Token::Comma => write!(f, "\",\""),
+            Token::Open => write!(f, "\"(\""),
+            Token::Close => write!(f, "\")\""),
+        }
+    }
+}
+
+// Splits a line into tokens, up to a comment. Letters and digits are read
+// separately, so "3x" is a number and a word.
+fn tokenize(line: &str) -> Result<Vec<Token>> {
+    let mut tokens = Vec::new();
+    let mut chars = line.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '#' => break,
+            ',' => tokens.push(Token::Comma),
+            '(' => tokens.push(Token::Open),
+            ')' => tokens.push(Token::Close),
+            c if c.is_whitespace() => (),
+            c if c.is_ascii_digit() => {
+                let mut number = String::from(c);
+                while let Some(digit) = chars.next_if(|c| c.is_ascii_digit()) {
+                    number.push(digit);
+                }
+                tokens.push(Token::Number(
+                    number
+                        .parse()
+                        .with_context(|| format!("Number too large: {}", number))?,
+                ));
+            }
+            c if c.is_ascii_alphabetic() => {
+                let mut word = String::from(c);
+                while let Some(letter) = chars.next_if(|c| c.is_ascii_alphabetic()) {
+                    word.push(letter);
+                }
+                tokens.push(Token::Word(word));
+            }
+            c => return Err(anyhow!("Unexpected character: {:?}", c)),
+        }
+    }
+    Ok(tokens)
+}
+
+fn parse_line(line: &str) -> Result<Vec<Motion>> {
+    let tokens = tokenize(line)?;
+    if tokens.is_empty() {
+        return Ok(Vec::new());
+    }
+    let mut parser = Parser {
+        tokens: tokens.into_iter().peekable(),
+    };
+    let motions = parser.items()?;
+    match parser.tokens.next() {
+        None => Ok(motions),
+        Some(token) => Err(anyhow!("Unexpected {} after the last motion", token)),
+    }
+}
+
+// Recursive descent, one function per rule of the grammar
+struct Parser {
+    tokens: std::iter::Peekable<std::vec::IntoIter<Token>>,
+}
+
+impl Parser {
+    fn items(&mut self) -> Result<Vec<Motion>> {
+        let mut motions = self.item()?;
+        while self.tokens.next_if_eq(&Token::Comma).is_some() {
+            motions.extend(self.item()?);
+            check_length(motions.len())?;
+        }
+        Ok(motions)
+    }
+
+    fn item(&mut self) -> Result<Vec<Motion>> {
+        match self.tokens.next() {
+            Some(Token::Number(count)) => {
+                self.expect(Token::Word(String::from("x")))?;
+                self.expect(Token::Open)?;
+                let block = self.items()?;
+                self.expect(Token::Close)?;
+                let total = block.len().saturating_mul(count as usize);
+                check_length(total)?;
+                Ok(block.iter().cycle().take(total).cloned().collect())
+            }
+            Some(Token::Word(direction)) => {
+                let direction = Direction::try_from(direction.as_str())?;
+                match self.tokens.next() {
+                    Some(Token::Number(steps)) => Ok(vec![Motion { direction, steps }]),
+                    Some(token) => Err(anyhow!("Expected a number of steps, found {}", token)),
+                    None => Err(anyhow!("Expected a number of steps")),
+                }
+            }
+            Some(token) => Err(anyhow!("Expected a motion or a repeat, found {}", token)),
+            None => Err(anyhow!("Expected a motion or a repeat")),
+        }
+    }
+
+    fn expect(&mut self, expected: Token) -> Result<()> {
+        match self.tokens.next() {
+            Some(token) if token == expected => Ok(()),
+            Some(token) => Err(anyhow!("Expected {}, found {}", expected, token)),
+            None => Err(anyhow!("Expected {}", expected)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn script(text: &str) -> Result<Vec<String>> {
+        let lines: Vec<String> = text.lines().map(String::from).collect();
+        Ok(parse_script(&lines)?
+            .iter()
+            .map(|motion| motion.to_string())
+            .collect())
+    }
+
+    #[test]
+    fn puzzle_input() -> Result<()> {
+        assert_eq!(script("R 4\nU 4\nL 3")?, vec!["R 4", "U 4", "L 3"]);
+        Ok(())
+    }
+
+    #[test]
+    fn diagonals_and_comments() -> Result<()> {
+        assert_eq!(
+            script("# A scenario\nUR 2, DL 1 # back a bit\n\n  UL 3,DR 10")?,
+            vec!["UR 2", "DL 1", "UL 3", "DR 10"]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn repeats() -> Result<()> {
+        assert_eq!(
+            script("3x(R 2, U 1)")?,
+            vec!["R 2", "U 1", "R 2", "U 1", "R 2", "U 1"]
+        );
+        assert_eq!(
+            script("L 1, 2 x (D 1, 2x(R 1)), U 5")?,
+            vec!["L 1", "D 1", "R 1", "R 1", "D 1", "R 1", "R 1", "U 5"]
+        );
+        assert_eq!(script("0x(R 1)")?, Vec::<String>::new());
+        Ok(())
+    }
+
+    #[test]
+    fn errors() {
+        let error = |text: &str| format!("{:#}", parse_script(&[text.to_owned()]).unwrap_err());
+        assert_eq!(
+            error("R 1, X 2"),
+            "Line 1: \"R 1, X 2\": Not a valid direction: X"
+        );
+        assert!(error("3x(R 2").ends_with("Expected \")\""));
+        assert!(error("3(R 2)").ends_with("Expected \"x\", found \"(\""));
+        assert!(error("R 2 U 1").ends_with("Unexpected \"U\" after the last motion"));
+        assert!(error("R").ends_with("Expected a number of steps"));
+        assert!(error("R 2,").ends_with("Expected a motion or a repeat"));
+        assert!(error("R -2").ends_with("Unexpected character: '-'"));
+    }
+
+    #[test]
+    fn too_many_motions() -> Result<()> {
+        let error = |text: &str| format!("{:#}", parse_script(&[text.to_owned()]).unwrap_err());
+        assert!(error("4000000000x(R 1)").ends_with("More than 1000000 motions"));
+        assert!(error("1000x(1000x(1000x(R 1)))").ends_with("More than 1000000 motions"));
+        assert!(error("1000000x(R 1), U 1").ends_with("More than 1000000 motions"));
+        let lines = vec!["1000000x(R 1)".to_owned(), "U 1".to_owned()];
+        assert_eq!(
+            parse_script(&lines).unwrap_err().to_string(),
+            "More than 1000000 motions"
+        );
+        // Nothing repeated any number of times is still nothing
+        assert_eq!(script("4000000000x(0x(R 1))")?, Vec::<String>::new());
+        assert_eq!(parse_script(&lines[..1])?.len(), 1_000_000);
+        Ok(())
+    }
+
+    #[test]
+    fn too_many_steps() -> Result<()> {
+        let lines = vec!["R 4294967295".to_owned()];
+        assert_eq!(
+            parse_script(&lines).unwrap_err().to_string(),
+            "More than 10000000 steps"
+        );
+        let lines = vec!["5x(R 1000000)".to_owned(), "2x(U 2500001)".to_owned()];
+        assert_eq!(
+            parse_script(&lines).unwrap_err().to_string(),
+            "More than 10000000 steps"
+        );
+        assert_eq!(parse_script(&lines[..1])?.len(), 5);
+        Ok(())
+    }
+
+    #[test]
+    fn diagonal_steps() -> Result<()> {
+        use crate::Rope;
+        let mut rope = Rope::new(3, &[2])?;
+        for motion in parse_script(&["UR 3".to_owned()])? {
+            rope.movement(&motion);
+        }
+        // Each knot follows diagonally right behind the one before it
+        assert_eq!(rope.knots(), [(3, 3), (2, 2), (1, 1)]);
+        assert_eq!(rope.visited_count(2), Some(2));
+        Ok(())
+    }
+}